@@ -29,12 +29,22 @@ impl<'a> super::Backbone {
 		let type_id = component.get_type_id();
 		let comps = self.comps.get_mut(&node_id).unwrap();
 		
+		// Components of an entered node are loaded while attached.
+		let loaded = self.path.contains(&node_id);
+		
 		if let Some(mut old) = comps.insert(type_id, Box::new(component)) {
+			if loaded {
+				old.on_unload();
+			}
 			old.on_detachment(node_id);
 		}
 		
 		comps.get_mut(&type_id).unwrap().on_attachment(node_id);
 		
+		if loaded {
+			comps.get_mut(&type_id).unwrap().on_load();
+		}
+		
 		match comps.get_mut(&type_id).unwrap().downcast_mut::<C>() {
 			None => panic!("Could not fetch component immediately after attaching it."),
 			Some(comp) => unsafe {
@@ -44,8 +54,34 @@ impl<'a> super::Backbone {
 		}
 	}
 	
+	/// Detaches the component of the given type from the given node, returning it.
+	///
+	/// If the node is part of the current path, the component is unloaded before being detached.
+	pub fn node_component_detach<C: Component>(&mut self, node_id: NodeId) -> Option<Box<C>> {
+		let loaded = self.path.contains(&node_id);
+		
+		let comps = self.comps.get_mut(&node_id)?;
+		let mut component = comps.remove(&TypeId::of::<C>())?;
+		
+		if comps.is_empty() {
+			self.comps.remove(&node_id);
+		}
+		
+		if loaded {
+			component.on_unload();
+		}
+		
+		component.on_detachment(node_id);
+		component.downcast::<C>().ok()
+	}
+	
+	/// Returns the type names of the components attached to the given node, sorted by name.
+	pub fn node_component_names(&self, node_id: NodeId) -> Vec<&'static str> {
+		get_component_names(&self.comps, node_id)
+	}
+	
 	pub fn set_root_node_handler(&mut self, handler: Box<dyn Handler>) {
-		self.handlers.insert(self.root_id, handler);
+		self.handler_insert(self.root_id, handler);
 	}
 }
 
@@ -75,114 +111,240 @@ impl<'a> super::Backbone {
 	pub fn component_get_mut_by_node<C: Component>(&mut self, node_id: NodeId) -> Result<&'a mut C, ComponentAccessError> {
 		get_component_mut(&self.nodes, &mut self.comps, node_id)
 	}
+	
+	/// Returns two components of different types at once, like `component_get_mut`.
+	pub fn component_get_pair_mut<A: Component, B: Component>(&mut self) -> Result<(&'a mut A, &'a mut B), ComponentAccessError> {
+		let start_id = match self.location_get_node() {
+			Some(x) => x,
+			None => return Err(ComponentAccessError::PathIsNull)
+		};
+		
+		get_component_pair_mut(&self.nodes, &mut self.comps, start_id)
+	}
+	
+	/// Returns the component of the nearest ancestor of the given node that has one, and the id of that ancestor.
+	pub fn component_get_ancestor<C: Component>(&self, node_id: NodeId) -> Result<(NodeId, &'a C), ComponentAccessError> {
+		get_ancestor_component(&self.nodes, &self.comps, node_id)
+	}
+	
+	/// Returns all components of the given type, and the ids of their nodes, ordered by node id.
+	pub fn component_query<C: Component>(&self) -> Vec<(NodeId, &'a C)> {
+		query_components(&self.comps)
+	}
+	
+	/// Returns all components of the given type mutably, and the ids of their nodes, ordered by node id.
+	pub fn component_query_mut<C: Component>(&mut self) -> Vec<(NodeId, &'a mut C)> {
+		query_components_mut(&mut self.comps)
+	}
 }
 
 impl<'a> super::Context<'a> {
 	pub fn component_get<C: Component>(&self) -> Result<&'a C, ComponentAccessError> {
-		get_component(&self.nodes, &self.comps, self.current)
+		get_component(self.nodes, self.comps, self.current)
 	}
 	
 	pub fn component_get_by_node<C: Component>(&self, node_id: NodeId) -> Result<&'a C, ComponentAccessError> {
-		get_component(&self.nodes, &self.comps, node_id)
+		get_component(self.nodes, self.comps, node_id)
 	}
 	
 	pub fn component_get_mut<C: Component>(&mut self) -> Result<&'a mut C, ComponentAccessError> {
-		get_component_mut(&self.nodes, &mut self.comps, self.current)
+		get_component_mut(self.nodes, self.comps, self.current)
 	}
 	
 	pub fn component_get_mut_by_node<C: Component>(&mut self, node_id: NodeId) -> Result<&'a mut C, ComponentAccessError> {
-		get_component_mut(&self.nodes, &mut self.comps, node_id)
+		get_component_mut(self.nodes, self.comps, node_id)
+	}
+	
+	/// Returns two components of different types at once, like `component_get_mut`.
+	pub fn component_get_pair_mut<A: Component, B: Component>(&mut self) -> Result<(&'a mut A, &'a mut B), ComponentAccessError> {
+		get_component_pair_mut(self.nodes, self.comps, self.current)
+	}
+	
+	/// Returns the component of the nearest ancestor of the given node that has one, and the id of that ancestor.
+	pub fn component_get_ancestor<C: Component>(&self, node_id: NodeId) -> Result<(NodeId, &'a C), ComponentAccessError> {
+		get_ancestor_component(self.nodes, self.comps, node_id)
+	}
+	
+	/// Returns all components of the given type, and the ids of their nodes, ordered by node id.
+	pub fn component_query<C: Component>(&self) -> Vec<(NodeId, &'a C)> {
+		query_components(self.comps)
+	}
+	
+	/// Returns all components of the given type mutably, and the ids of their nodes, ordered by node id.
+	pub fn component_query_mut<C: Component>(&mut self) -> Vec<(NodeId, &'a mut C)> {
+		query_components_mut(self.comps)
 	}
 }
 
-fn get_component<'a, C: Component>(nodes: &super::Nodes, comps: &super::Comps, node_id: NodeId) -> Result<&'a C, ComponentAccessError> {
-	match comps.get(&node_id) {
-		None => {
-			let node_parent_id = nodes.get(&node_id).unwrap().get_parent_id();
-			if node_id == node_parent_id {
-				Err(ComponentAccessError::DoesNotExist)
-			} else {
-				get_component(nodes, comps, node_parent_id)
-			}
-		},
+pub(crate) fn get_component_names(comps: &Comps, node_id: NodeId) -> Vec<&'static str> {
+	let mut names: Vec<&'static str> = match comps.get(&node_id) {
+		Some(node_comps) => node_comps.values().map(|comp| comp.get_type_name()).collect(),
+		None => vec![],
+	};
+	
+	names.sort_unstable();
+	names
+}
+
+/// Walks from the given node towards the root, returning the first node with a component of the type `C`.
+fn find_component<C: Component>(nodes: &super::Nodes, comps: &super::Comps, node_id: NodeId) -> Result<NodeId, ComponentAccessError> {
+	let type_id = TypeId::of::<C>();
+	let mut current_id = node_id;
+	
+	loop {
+		if comps.get(&current_id).is_some_and(|node_comps| node_comps.contains_key(&type_id)) {
+			return Ok(current_id)
+		}
 		
-		Some(node_comps) => {
-			let type_id = TypeId::of::<C>();
-			match node_comps.get(&type_id) {
-				None => {
-					let node_parent_id = nodes.get(&node_id).unwrap().get_parent_id();
-					if node_id == node_parent_id {
-						Err(ComponentAccessError::DoesNotExist)
-					} else {
-						get_component(nodes, comps, node_parent_id)
-					}
-				},
-				
-				Some(comp) => {
-					match comp.downcast_ref::<C>() {
-						None => Err(ComponentAccessError::CantDowncast),
-						Some(comp) => unsafe {
-							// WARNING: Do not try this at home!
-							Ok(transmute::<&C, &'a C>(comp.borrow()))
-						}
-					}
-				}
-			}
+		let parent_id = match nodes.get(&current_id) {
+			Some(node) => node.get_parent_id(),
+			None => return Err(ComponentAccessError::UnknownNode(current_id)),
+		};
+		
+		if parent_id == current_id {
+			return Err(ComponentAccessError::DoesNotExist {
+				type_name: std::any::type_name::<C>(),
+				node: node_id,
+			})
+		}
+		
+		current_id = parent_id;
+	}
+}
+
+fn get_component<'a, C: Component>(nodes: &super::Nodes, comps: &super::Comps, node_id: NodeId) -> Result<&'a C, ComponentAccessError> {
+	let holder_id = find_component::<C>(nodes, comps, node_id)?;
+	
+	match comps[&holder_id][&TypeId::of::<C>()].downcast_ref::<C>() {
+		None => Err(ComponentAccessError::CantDowncast {
+			type_name: std::any::type_name::<C>(),
+			node: holder_id,
+		}),
+		Some(comp) => unsafe {
+			// WARNING: Do not try this at home!
+			Ok(transmute::<&C, &'a C>(comp.borrow()))
 		}
 	}
 }
 
 fn get_component_mut<'a, C: Component>(nodes: &super::Nodes, comps: &mut super::Comps, node_id: NodeId) -> Result<&'a mut C, ComponentAccessError> {
-	match comps.get_mut(&node_id) {
-		None => {
-			let node_parent_id = nodes.get(&node_id).unwrap().get_parent_id();
-			if node_id == node_parent_id {
-				Err(ComponentAccessError::DoesNotExist)
-			} else {
-				get_component_mut(nodes, comps, node_parent_id)
-			}
-		},
-		
-		Some(node_comps) => {
-			let type_id = TypeId::of::<C>();
-			match node_comps.get_mut(&type_id) {
-				None => {
-					let node_parent_id = nodes.get(&node_id).unwrap().get_parent_id();
-					if node_id == node_parent_id {
-						Err(ComponentAccessError::DoesNotExist)
-					} else {
-						get_component_mut(nodes, comps, node_parent_id)
-					}
-				},
-				
-				Some(comp) => {
-					match comp.downcast_mut::<C>() {
-						None => Err(ComponentAccessError::CantDowncast),
-						Some(comp) => unsafe {
-							// WARNING: Do not try this at home!
-							Ok(transmute::<&mut C, &'a mut C>(comp.borrow_mut()))
-						}
-					}
-				}
-			}
+	let holder_id = find_component::<C>(nodes, comps, node_id)?;
+	
+	let comp = comps.get_mut(&holder_id)
+		.and_then(|node_comps| node_comps.get_mut(&TypeId::of::<C>()))
+		.and_then(|comp| comp.downcast_mut::<C>());
+	
+	match comp {
+		None => Err(ComponentAccessError::CantDowncast {
+			type_name: std::any::type_name::<C>(),
+			node: holder_id,
+		}),
+		Some(comp) => unsafe {
+			// WARNING: Do not try this at home!
+			Ok(transmute::<&mut C, &'a mut C>(comp.borrow_mut()))
 		}
 	}
 }
 
+fn get_component_pair_mut<'a, A: Component, B: Component>(nodes: &super::Nodes, comps: &mut super::Comps, node_id: NodeId) -> Result<(&'a mut A, &'a mut B), ComponentAccessError> {
+	// Components are unique per node and type, so only the same type can yield the same component twice.
+	if TypeId::of::<A>() == TypeId::of::<B>() {
+		return Err(ComponentAccessError::AliasedBorrow {
+			type_name: std::any::type_name::<A>(),
+		})
+	}
+	
+	let a = get_component_mut::<A>(nodes, comps, node_id)?;
+	let b = get_component_mut::<B>(nodes, comps, node_id)?;
+	Ok((a, b))
+}
+
+fn get_ancestor_component<'a, C: Component>(nodes: &super::Nodes, comps: &super::Comps, node_id: NodeId) -> Result<(NodeId, &'a C), ComponentAccessError> {
+	let parent_id = match nodes.get(&node_id) {
+		Some(node) => node.get_parent_id(),
+		None => return Err(ComponentAccessError::UnknownNode(node_id)),
+	};
+	
+	// The root node has no ancestors.
+	if parent_id == node_id {
+		return Err(ComponentAccessError::DoesNotExist {
+			type_name: std::any::type_name::<C>(),
+			node: node_id,
+		})
+	}
+	
+	let holder_id = find_component::<C>(nodes, comps, parent_id)?;
+	Ok((holder_id, get_component(nodes, comps, holder_id)?))
+}
+
+fn query_components<'a, C: Component>(comps: &super::Comps) -> Vec<(NodeId, &'a C)> {
+	let type_id = TypeId::of::<C>();
+	
+	let mut found: Vec<(NodeId, &'a C)> = comps.iter()
+		.filter_map(|(node_id, node_comps)| {
+			let comp = node_comps.get(&type_id)?.downcast_ref::<C>()?;
+			
+			// WARNING: Do not try this at home!
+			Some((*node_id, unsafe {transmute::<&C, &'a C>(comp)}))
+		})
+		.collect();
+	
+	found.sort_by_key(|(node_id, _)| node_id.get_inner());
+	found
+}
+
+fn query_components_mut<'a, C: Component>(comps: &mut super::Comps) -> Vec<(NodeId, &'a mut C)> {
+	let type_id = TypeId::of::<C>();
+	
+	let mut found: Vec<(NodeId, &'a mut C)> = comps.iter_mut()
+		.filter_map(|(node_id, node_comps)| {
+			let comp = node_comps.get_mut(&type_id)?.downcast_mut::<C>()?;
+			
+			// WARNING: Do not try this at home!
+			Some((*node_id, unsafe {transmute::<&mut C, &'a mut C>(comp)}))
+		})
+		.collect();
+	
+	found.sort_by_key(|(node_id, _)| node_id.get_inner());
+	found
+}
+
 /// Errors that may occur when accessing components.
+#[derive(Clone, PartialEq, Debug)]
 pub enum ComponentAccessError {
+	/// The backbone has no location to start looking from.
 	PathIsNull,
-	DoesNotExist,
-	CantDowncast,
+	
+	/// Neither the given node nor its ancestors have a component of the given type.
+	DoesNotExist {
+		type_name: &'static str,
+		node: NodeId,
+	},
+	
+	/// The component of the given node could not be downcast to the given type.
+	CantDowncast {
+		type_name: &'static str,
+		node: NodeId,
+	},
+	
+	/// The given node does not exist.
+	UnknownNode(NodeId),
+	
+	/// The same component was requested twice, for a pair of mutable references.
+	AliasedBorrow {
+		type_name: &'static str,
+	},
 }
 
 impl std::fmt::Display for ComponentAccessError {
 	fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
-		write!(f, "{}", match self {
-			ComponentAccessError::PathIsNull => "Path is empty",
-			ComponentAccessError::DoesNotExist => "Does not exist",
-			ComponentAccessError::CantDowncast => "Cant downcast",
-		})
+		match self {
+			ComponentAccessError::PathIsNull => write!(f, "Path is empty"),
+			ComponentAccessError::DoesNotExist {type_name, node} => write!(f, "No {} at or above node {}", type_name, node),
+			ComponentAccessError::CantDowncast {type_name, node} => write!(f, "Cant downcast the component of node {} to {}", node, type_name),
+			ComponentAccessError::UnknownNode(node) => write!(f, "Node {} does not exist", node),
+			ComponentAccessError::AliasedBorrow {type_name} => write!(f, "Cant borrow {} twice", type_name),
+		}
 	}
 }
 
@@ -307,3 +469,78 @@ impl<T> std::ops::DerefMut for WrapperComponent<T> {
 		&mut self.inner
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::Backbone;
+	use super::super::testing::{Log, Logger, go};
+	
+	#[test]
+	fn detach() {
+		let log = Log::default();
+		let mut backbone = Backbone::new();
+		let root = backbone.root_get_id();
+		let a = backbone.node_new(root, "a", None).unwrap();
+		let b = backbone.node_new(root, "b", None).unwrap();
+		
+		go(&mut backbone, "/a");
+		
+		// Components of an entered node are unloaded before being detached.
+		backbone.node_component_attach(a, Logger::new("a", &log));
+		assert!(backbone.node_component_detach::<Logger>(a).is_some());
+		assert!(backbone.node_component_detach::<Logger>(a).is_none());
+		assert!(backbone.node_component_names(a).is_empty());
+		assert_eq!(log.replace(vec![]), vec!["attach a", "load a", "unload a", "detach a"]);
+		
+		backbone.node_component_attach(b, Logger::new("b", &log));
+		assert!(backbone.node_component_detach::<Logger>(b).is_some());
+		assert_eq!(log.replace(vec![]), vec!["attach b", "detach b"]);
+	}
+	
+	#[test]
+	fn queries() {
+		let mut backbone = Backbone::new();
+		let root = backbone.root_get_id();
+		let a = backbone.node_new(root, "a", None).unwrap();
+		let b = backbone.node_new(a, "b", None).unwrap();
+		let c = backbone.node_new(root, "c", None).unwrap();
+		
+		backbone.node_component_attach(root, WrapperComponent::new("Number", 1));
+		backbone.node_component_attach(a, WrapperComponent::new("Number", 2));
+		backbone.node_component_attach(b, WrapperComponent::new("Text", "b"));
+		backbone.node_component_attach(c, WrapperComponent::new("Number", 3));
+		
+		let numbers: Vec<_> = backbone.component_query::<WrapperComponent<i32>>().into_iter()
+			.map(|(node_id, number)| (node_id, **number))
+			.collect();
+		assert_eq!(numbers, vec![(root, 1), (a, 2), (c, 3)]);
+		
+		for (_, number) in backbone.component_query_mut::<WrapperComponent<i32>>() {
+			**number *= 10;
+		}
+		
+		let (holder, number) = backbone.component_get_ancestor::<WrapperComponent<i32>>(b).unwrap();
+		assert_eq!((holder, **number), (a, 20));
+		let (holder, number) = backbone.component_get_ancestor::<WrapperComponent<i32>>(a).unwrap();
+		assert_eq!((holder, **number), (root, 10));
+		
+		assert_eq!(backbone.component_get_ancestor::<WrapperComponent<&str>>(b).err(), Some(ComponentAccessError::DoesNotExist {
+			type_name: std::any::type_name::<WrapperComponent<&str>>(),
+			node: a,
+		}));
+		
+		backbone.location_set("/a/b").unwrap();
+		backbone.update_until_idle();
+		
+		let (number, text) = backbone.component_get_pair_mut::<WrapperComponent<i32>, WrapperComponent<&str>>().unwrap();
+		**number += 1;
+		**text = "changed";
+		assert_eq!(**backbone.component_get_by_node::<WrapperComponent<i32>>(a).unwrap(), 21);
+		assert_eq!(**backbone.component_get::<WrapperComponent<&str>>().unwrap(), "changed");
+		
+		assert_eq!(backbone.component_get_pair_mut::<WrapperComponent<i32>, WrapperComponent<i32>>().err(), Some(ComponentAccessError::AliasedBorrow {
+			type_name: std::any::type_name::<WrapperComponent<i32>>(),
+		}));
+	}
+}
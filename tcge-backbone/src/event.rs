@@ -1,5 +1,7 @@
 use super::NodeId;
 use super::State;
+use super::Subscriptions;
+use super::nodes::{get_tree_order, get_ancestry};
 
 /// Collection type for `Handler` instances, attached to `Node` instances by id.
 pub type Handlers = super::FxHashMap<NodeId, Box<dyn Handler>>;
@@ -7,7 +9,14 @@ pub type Handlers = super::FxHashMap<NodeId, Box<dyn Handler>>;
 // Implementation details regarding nodes.
 impl super::Backbone {
 	
+	/// Fires the given event, followed by the events that were queued meanwhile.
 	pub fn fire_event(&mut self, event: &mut dyn Event) {
+		self.fire_event_now(event);
+		self.event_queue_drain();
+	}
+	
+	/// Fires the given event, without draining the event queue afterwards.
+	pub(crate) fn fire_event_now(&mut self, event: &mut dyn Event) {
 		
 		// Non-Passive events can only be fired if the backbone is idle.
 		if ! event.is_passive() {
@@ -19,116 +28,178 @@ impl super::Backbone {
 		let mut event = Wrapper::new(event);
 		self.fire_event_impl(&mut event, 0);
 		
-		if event.new_state == State::Idle {
+		if event.flow.new_state == State::Idle {
 			return
 		}
 		
 		if self.state.can_replace() {
-			self.state = event.new_state;
+			self.state = event.flow.new_state;
 		}
 	}
 	
-	fn fire_event_impl(&mut self, event: &mut Wrapper, depth: usize) {
+	/// Sets the handler of the given node, subscribing its listeners.
+	pub(crate) fn handler_insert(&mut self, node_id: NodeId, handler: Box<dyn Handler>) {
+		let mut subscriptions = Subscriptions::new();
+		handler.subscribe(&mut subscriptions);
 		
-		// If the backbone path is empty, we cant do anything.
-		if self.path.is_empty() {
-			return
+		self.listeners.insert(node_id, subscriptions.into_listeners());
+		self.handlers.insert(node_id, handler);
+	}
+	
+	/// Fires a `LifecycleEvent` for the given node, returning the veto of a handler, if any.
+	///
+	/// Only `State::Stop` and `State::Move` are vetoes, any other state a handler asks for is ignored.
+	pub(crate) fn lifecycle_fire(&mut self, node_id: NodeId, lifecycle: Lifecycle) -> Option<State> {
+		let mut event = LifecycleEvent {
+			node: node_id,
+			lifecycle,
+		};
+		
+		let mut event = Wrapper::new(&mut event);
+		self.fire_event_impl(&mut event, 0);
+		
+		match event.flow.new_state {
+			state @ State::Stop(_) | state @ State::Move(_) => Some(state),
+			_ => None,
 		}
+	}
+	
+	fn fire_event_impl(&mut self, event: &mut Wrapper, depth: usize) {
 		
-		// Due to the above call, this line can't fail.
-		let target_id = self.location_get_node().unwrap();
+		// The nodes the event flows trough, and the node it is evaluated by.
+		let (route, target_id) = match event.event.get_dispatch() {
+			Dispatch::Path => {
+				// If the backbone path is empty, we cant do anything.
+				if self.path.is_empty() {
+					return
+				}
+				
+				// Due to the above call, this line can't fail.
+				(Route::Path, self.location_get_node().unwrap())
+			},
+			
+			Dispatch::Broadcast => {
+				if self.tree_positions.is_none() {
+					let order = get_tree_order(&self.nodes, self.root_id);
+					self.tree_positions = Some(order.into_iter().enumerate().map(|(position, id)| (id, position)).collect());
+				}
+				
+				(Route::Tree, self.location_get_node().unwrap_or(self.root_id))
+			},
+			
+			Dispatch::Target(target_id) => match get_ancestry(&self.nodes, target_id) {
+				Some(nodes) => (Route::Ancestry(nodes), target_id),
+				None => return,
+			},
+		};
 		
 		// --- Event Propagation
-		self.fire_event_propagate(event, target_id);
+		if event.can_propagate {
+			self.fire_event_phase(event, target_id, Phase::Propagation, &route, |flow| flow.can_propagate);
+		}
 		
 		// --- Event Action
-		self.fire_event_action(event, target_id);
+		if event.can_default {
+			self.fire_event_phase(event, target_id, Phase::Action, &route, |flow| flow.can_default);
+		}
 		
 		// --- Event Bubbling
-		self.fire_event_bubbling(event, target_id);
+		if event.can_bubble {
+			self.fire_event_phase(event, target_id, Phase::Bubbling, &route, |flow| flow.can_bubble);
+		}
 		
 		self.fire_event_next(event, depth);
 	}
 	
-	fn fire_event_propagate(&mut self, mut event: &mut Wrapper, target_id: NodeId) {
-		if !event.can_propagate {
-			return
+	/// Returns the position of the given node along the route of an event, if it is on the route.
+	fn route_position(&self, route: &Route, node_id: NodeId) -> Option<usize> {
+		match route {
+			Route::Path => self.path.iter().position(|id| *id == node_id),
+			Route::Tree => self.tree_positions.as_ref()?.get(&node_id).cloned(),
+			Route::Ancestry(nodes) => nodes.iter().position(|id| *id == node_id),
 		}
+	}
+	
+	/// Calls the listeners for the event that are on its route in the given phase,
+	/// by descending priority and then in route order, for as long as `can_continue` allows.
+	fn fire_event_phase(&mut self, event: &mut Wrapper, target_id: NodeId, phase: Phase, route: &Route, can_continue: fn(&Flow) -> bool) {
+		event.flow.phase = phase;
+		
+		let event_type = mopa::Any::get_type_id(&*event.event);
 		
-		event.phase = Phase::Propagation;
+		// The buffer is reused between phases and events, to not allocate on every dispatch.
+		let mut calls = std::mem::take(&mut self.dispatch_buffer);
+		calls.clear();
 		
-		for node_id in self.path.iter() {
-			let handler = self.handlers.get_mut(node_id);
+		for (sequence, &(node_id, index)) in self.listeners.get_subscribers(event_type).enumerate() {
+			let listener = match self.listeners.get(node_id, index) {
+				Some(listener) if listener.get_phases().contains(&phase) => listener,
+				_ => continue,
+			};
 			
-			let mut context = Context {
-				target: target_id,
-				current: *node_id,
-				nodes: &self.nodes,
-				comps: &mut self.comps,
-				path: &self.path,
-				path_str: &self.path_str.as_str(),
+			let position = match phase {
+				Phase::Action if node_id == target_id => 0,
+				Phase::Action => continue,
+				_ => match self.route_position(route, node_id) {
+					Some(position) => position,
+					None => continue,
+				},
 			};
 			
-			if let Some(handler) = handler { handler.on_event(&mut event, &mut context); }
+			// Bubbling runs along the route in reverse.
+			let position = match phase {
+				Phase::Bubbling => usize::MAX - position,
+				_ => position,
+			};
 			
-			if !event.can_propagate {
-				break;
-			}
-		}
-	}
-	
-	fn fire_event_action(&mut self, mut event: &mut Wrapper, target_id: NodeId) {
-		if ! event.can_default {
-			return
+			calls.push(Call {
+				priority: listener.get_priority(),
+				position,
+				sequence,
+				node_id,
+				index,
+			});
 		}
 		
-		event.phase = Phase::Action;
+		if calls.len() > 1 {
+			calls.sort_unstable_by_key(|call| (std::cmp::Reverse(call.priority), call.position, call.sequence));
+		}
 		
-		if let Some(handler) = self.handlers.get_mut(&target_id) {
-			let mut context = Context {
-				target: target_id,
-				current: target_id,
-				nodes: &self.nodes,
-				comps: &mut self.comps,
-				path: &self.path,
-				path_str: &self.path_str.as_str(),
+		for call in calls.iter() {
+			if !can_continue(&event.flow) {
+				break;
+			}
+			
+			let handler = match self.handlers.get_mut(&call.node_id) {
+				Some(handler) => handler,
+				None => continue,
 			};
 			
-			handler.on_event(
-				&mut event,
-				&mut context
-			);
-		}
-	}
-	
-	fn fire_event_bubbling(&mut self, mut event: &mut Wrapper, target_id: NodeId) {
-		if !event.can_bubble {
-			return
-		}
-		
-		event.phase = Phase::Bubbling;
-		
-		for node_id in self.path.iter().rev() {
-			let handler = self.handlers.get_mut(node_id);
+			let listener = match self.listeners.get_mut(call.node_id, call.index) {
+				Some(listener) => listener,
+				None => continue,
+			};
 			
 			let mut context = Context {
 				target: target_id,
-				current: *node_id,
+				current: call.node_id,
 				nodes: &self.nodes,
 				comps: &mut self.comps,
 				path: &self.path,
-				path_str: &self.path_str.as_str(),
+				path_str: self.path_str.as_str(),
+				queue: &mut self.queue,
+				history: &self.history,
 			};
 			
-			if let Some(handler) = handler { handler.on_event(&mut event, &mut context); }
-			
-			if !event.can_bubble {
-				break;
+			if !listener.call(&mut **handler, &mut *event.event, &mut event.flow, &mut context) {
+				handler.on_event(event, &mut context);
 			}
 		}
+		
+		self.dispatch_buffer = calls;
 	}
 	
-	fn fire_event_next(&mut self, mut event: &mut Wrapper, depth: usize) {
+	fn fire_event_next(&mut self, event: &mut Wrapper, depth: usize) {
 		// This allows handlers to fire events in response to events.
 		while let State::Fire(_) = event.new_state {
 			if depth > 10 {
@@ -142,7 +213,7 @@ impl super::Backbone {
 				self.fire_event_impl(&mut sub_event, depth + 1);
 				
 				if sub_event.new_state != State::Idle {
-					event.new_state = sub_event.new_state;
+					event.flow.new_state = sub_event.flow.new_state;
 				}
 			}
 		}
@@ -151,6 +222,29 @@ impl super::Backbone {
 
 ////////////////////////////////////////////////////////////////////////////////
 
+/// The nodes an event flows trough.
+enum Route {
+	/// The current path.
+	Path,
+	
+	/// All nodes, in tree order.
+	Tree,
+	
+	/// The given nodes, from the root down.
+	Ancestry(Vec<NodeId>),
+}
+
+/// A listener to call while dispatching an event.
+pub(crate) struct Call {
+	priority: i32,
+	position: usize,
+	sequence: usize,
+	node_id: NodeId,
+	index: usize,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
 /// A limited version of the backbone, that only allows mutating components,
 /// intended for event processing.
 pub struct Context<'a> {
@@ -160,19 +254,44 @@ pub struct Context<'a> {
 	pub comps: &'a mut super::Comps,
 	pub path: &'a super::Path,
 	pub path_str: &'a str,
+	pub(crate) queue: &'a mut super::EventQueue,
+	pub(crate) history: &'a super::History,
 }
 
 ////////////////////////////////////////////////////////////////////////////////
 
 /// An event that can be fired into the backbone.
 pub trait Event: mopa::Any {
-	///	If an event is passive, it can be fired
-	///	regardless of what state the backbone is in.
+	/// If an event is passive, it can be fired
+	/// regardless of what state the backbone is in.
 	fn is_passive(&self) -> bool {false}
 	
+	/// Which nodes the event is delivered to; the current path by default.
+	fn get_dispatch(&self) -> Dispatch {
+		Dispatch::Path
+	}
+	
 	fn get_type_name(&self) -> &'static str;
 }
 
+/// How an event is delivered, as chosen by `Event::get_dispatch`.
+///
+/// The event always flows trough its nodes in the propagation phase, is evaluated by its
+/// target in the action phase, and then flows back trough its nodes in reverse order.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Dispatch {
+	/// Delivers the event along the current path, to the current location.
+	Path,
+	
+	/// Delivers the event to every node with a handler, in tree order,
+	/// with the current location (or the root, if there is none) as target.
+	Broadcast,
+	
+	/// Delivers the event along the ancestors of the given node to that node,
+	/// whether it was entered or not.
+	Target(NodeId),
+}
+
 // This is 100% necessary until `std::` provides Any for object-traits.
 mopafy!(Event);
 
@@ -209,13 +328,8 @@ impl std::fmt::Display for Phase {
 	}
 }
 
-/// Wraps an event as it is processed by the backbone.
-pub struct Wrapper<'a> {
-	#[allow(dead_code)]
-	/// The event being processed.
-	pub event: &'a mut dyn Event,
-	
-	// --- State for the event
+/// The state of an event as it is processed by the backbone, apart from the event itself.
+pub struct Flow {
 	phase: Phase,
 	
 	/// Can the event flow towards its destination?
@@ -231,32 +345,17 @@ pub struct Wrapper<'a> {
 	new_state: State,
 }
 
-impl<'a> Wrapper<'a> {
-	
-	pub fn new(event: &'a mut dyn Event) -> Self {
+impl Flow {
+	fn new() -> Self {
 		Self {
-			event,
-			
-			// Initial State
 			phase: Phase::Creation,
 			can_propagate: true,
 			can_default: true,
 			can_bubble: true,
-			
 			new_state: State::Idle,
 		}
 	}
 	
-	/// Downcast the wrapped event into the given type, if possible.
-	pub fn downcast<E: Event>(&mut self) -> Option<&E> {
-		self.event.downcast_ref()
-	}
-	
-	/// Downcast the wrapped event into the given type, if possible.
-	pub fn downcast_mut<E: Event>(&mut self) -> Option<&mut E> {
-		self.event.downcast_mut()
-	}
-	
 	/// Prevents the event from being evaluated by its destination.
 	pub fn prevent_default(&mut self) {
 		self.can_default = false;
@@ -287,14 +386,282 @@ impl<'a> Wrapper<'a> {
 	}
 }
 
+/// Wraps an event as it is processed by the backbone.
+///
+/// Dereferences to the `Flow` of the event, to control where it goes.
+pub struct Wrapper<'a> {
+	/// The event being processed.
+	pub event: &'a mut dyn Event,
+	
+	flow: Flow,
+}
+
+impl<'a> Wrapper<'a> {
+	
+	pub fn new(event: &'a mut dyn Event) -> Self {
+		Self {
+			event,
+			flow: Flow::new(),
+		}
+	}
+	
+	/// Downcast the wrapped event into the given type, if possible.
+	pub fn downcast<E: Event>(&mut self) -> Option<&E> {
+		self.event.downcast_ref()
+	}
+	
+	/// Downcast the wrapped event into the given type, if possible.
+	pub fn downcast_mut<E: Event>(&mut self) -> Option<&mut E> {
+		self.event.downcast_mut()
+	}
+}
+
+impl<'a> std::ops::Deref for Wrapper<'a> {
+	type Target = Flow;
+	
+	fn deref(&self) -> &Self::Target {
+		&self.flow
+	}
+}
+
+impl<'a> std::ops::DerefMut for Wrapper<'a> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.flow
+	}
+}
+
+/// Whether the backbone is entering or leaving a node.
+#[derive(Eq, PartialEq, Hash, Clone, Copy, Debug)]
+pub enum Lifecycle {
+	Enter,
+	Leave,
+}
+
+impl std::fmt::Display for Lifecycle {
+	fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			Lifecycle::Enter => write!(fmt, "Enter"),
+			Lifecycle::Leave => write!(fmt, "Leave"),
+		}
+	}
+}
+
+/// Fired while the backbone moves, for every node it is about to enter or leave.
+///
+/// The event targets the node in question, which is already part of the path when entering.
+/// Any handler along the path can veto the move by setting a new state:
+/// `State::Stop` stops the backbone, while `State::Move` redirects it elsewhere.
+/// Redirecting to `.` simply stays at the current location.
+/// Any other state is ignored, and leaving a node that is being deleted can't be vetoed at all.
+///
+/// The node is only entered or left if no handler objects,
+/// in which case its components are loaded or unloaded and `Handler::on_enter`/`on_leave` is called.
+pub struct LifecycleEvent {
+	pub node: NodeId,
+	pub lifecycle: Lifecycle,
+}
+
+impl Event for LifecycleEvent {
+	fn is_passive(&self) -> bool {
+		true
+	}
+	
+	fn get_type_name(&self) -> &'static str {
+		"LifecycleEvent"
+	}
+}
+
 /// A node-handler is effectively the 'brain' of a node.
 /// All user-logic and -state for a node is owned by the handler.
-pub trait Handler {
-	/// Called when the node receives an event.
-	/// Can return a new state for the backbone.
-	fn on_event<'a>(
+///
+/// By listening to `LifecycleEvent`, a handler can veto the backbone entering or leaving a node:
+/// setting `State::Stop` or `State::Move` as new state keeps the node from being entered or left.
+/// A vetoed node never sees `on_enter` or `on_leave`.
+pub trait Handler: mopa::Any {
+	/// Called when the node receives an event of any type,
+	/// if the handler subscribed to it with `Subscriptions::fallback`.
+	/// Can set a new state for the backbone.
+	fn on_event(
 		&mut self,
-		event: &mut Wrapper,
-		context: &mut Context,
-	);
+		_event: &mut Wrapper,
+		_context: &mut Context,
+	) {}
+	
+	/// Called once when the handler is given to the backbone, to subscribe typed listeners.
+	///
+	/// Unlike `on_event`, a listener is only called for the event type and phases it subscribed to.
+	fn subscribe(&self, _subscriptions: &mut Subscriptions) {}
+	
+	/// Called after the backbone moved into the node, and its components were loaded.
+	fn on_enter(&mut self, _context: &mut Context) {}
+	
+	/// Called before the backbone moves out of the node, and its components are unloaded.
+	fn on_leave(&mut self, _context: &mut Context) {}
+}
+
+// This is 100% necessary until `std::` provides Any for object-traits.
+mopafy!(Handler);
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::{Backbone, NodePath};
+	use super::super::testing::go;
+	use std::sync::{Arc, Mutex};
+	
+	struct Probe(Dispatch);
+	
+	impl Event for Probe {
+		fn get_dispatch(&self) -> Dispatch {
+			self.0
+		}
+		
+		fn get_type_name(&self) -> &'static str {
+			"Probe"
+		}
+	}
+	
+	struct Recorder(Arc<Mutex<Vec<String>>>);
+	
+	impl Handler for Recorder {
+		fn on_event(&mut self, event: &mut Wrapper, context: &mut Context) {
+			if event.downcast::<Probe>().is_some() {
+				let name = context.nodes.get(&context.current).map(|node| node.get_name()).unwrap_or("");
+				self.0.lock().unwrap().push(format!("{}:{}", event.get_phase(), name));
+			}
+		}
+		
+		fn subscribe(&self, subscriptions: &mut Subscriptions) {
+			subscriptions.fallback(&[Phase::Propagation, Phase::Action, Phase::Bubbling], 0);
+		}
+	}
+	
+	/// Never subscribes anything, so it must never be called.
+	struct Silent;
+	
+	impl Handler for Silent {
+		fn on_event(&mut self, _event: &mut Wrapper, _context: &mut Context) {
+			panic!("a handler without subscriptions received an event");
+		}
+	}
+	
+	/// Vetoes entering or leaving its own node with the given states.
+	struct Gate {
+		enter: Option<fn() -> State>,
+		leave: Option<fn() -> State>,
+		entered: bool,
+	}
+	
+	impl Gate {
+		fn handler(enter: Option<fn() -> State>, leave: Option<fn() -> State>) -> Option<Box<dyn Handler>> {
+			Some(Box::new(Gate {
+				enter,
+				leave,
+				entered: false,
+			}))
+		}
+		
+		fn on_lifecycle(&mut self, event: &mut LifecycleEvent, flow: &mut Flow, context: &mut Context) {
+			if event.node != context.current {
+				return
+			}
+			
+			let veto = match event.lifecycle {
+				Lifecycle::Enter => self.enter,
+				Lifecycle::Leave => self.leave,
+			};
+			
+			if let Some(veto) = veto {
+				flow.new_state(veto());
+			}
+		}
+	}
+	
+	impl Handler for Gate {
+		fn subscribe(&self, subscriptions: &mut Subscriptions) {
+			subscriptions.listen(&[Phase::Action], 0, Gate::on_lifecycle);
+		}
+		
+		fn on_enter(&mut self, _context: &mut Context) {
+			self.entered = true;
+		}
+	}
+	
+	#[test]
+	fn vetoes() {
+		let mut backbone = Backbone::new();
+		let root = backbone.root_get_id();
+		let locked = backbone.node_new(root, "locked", Gate::handler(Some(|| State::Move(NodePath::parse("/open"))), None)).unwrap();
+		backbone.node_new(root, "open", None).unwrap();
+		backbone.node_new(root, "fire", Gate::handler(Some(|| State::Fire(Box::new(Probe(Dispatch::Path)))), None)).unwrap();
+		let sticky = backbone.node_new(root, "sticky", Gate::handler(None, Some(|| State::Move(NodePath::parse("."))))).unwrap();
+		backbone.node_new(root, "stop", Gate::handler(Some(|| State::Stop(Some("Nope".to_string()))), None)).unwrap();
+		
+		// Entering is rolled back and redirected.
+		go(&mut backbone, "/locked");
+		assert_eq!(backbone.location_get_str(), "/open");
+		let gate = backbone.handlers.get(&locked).unwrap().downcast_ref::<Gate>().unwrap();
+		assert!(!gate.entered);
+		
+		// Other states are not vetoes.
+		go(&mut backbone, "/fire");
+		assert_eq!(backbone.location_get_str(), "/fire");
+		
+		go(&mut backbone, "/sticky");
+		go(&mut backbone, "/open");
+		assert_eq!(backbone.location_get_str(), "/sticky");
+		
+		// Deleting a node leaves it, veto or not.
+		backbone.node_delete(sticky).unwrap();
+		assert_eq!(backbone.location_get_str(), "/");
+		
+		go(&mut backbone, "/stop");
+		assert_eq!(backbone.location_get_str(), "/");
+		match backbone.get_state() {
+			State::Stop(Some(reason)) => assert_eq!(reason, "Nope"),
+			state => panic!("expected the backbone to stop, but it is {}", state),
+		}
+		
+		// The very first move enters the root, which can be vetoed too.
+		let mut backbone = Backbone::new();
+		backbone.set_root_node_handler(Gate::handler(Some(|| State::Stop(None)), None).unwrap());
+		backbone.update_until_idle();
+		assert!(backbone.location_get().is_empty());
+		assert!(*backbone.get_state() == State::Stop(None));
+	}
+	
+	#[test]
+	fn dispatch() {
+		let calls = Arc::new(Mutex::new(vec![]));
+		let recorder = || Some(Box::new(Recorder(calls.clone())) as Box<dyn Handler>);
+		
+		let mut backbone = Backbone::new();
+		let root = backbone.root_get_id();
+		let a = backbone.node_new(root, "a", recorder()).unwrap();
+		backbone.node_new(a, "b", recorder()).unwrap();
+		let c = backbone.node_new(root, "c", None).unwrap();
+		let d = backbone.node_new(c, "d", recorder()).unwrap();
+		
+		let fire = |backbone: &mut Backbone, dispatch| {
+			backbone.fire_event(&mut Probe(dispatch));
+			calls.lock().unwrap().drain(..).collect::<Vec<_>>().join(" ")
+		};
+		
+		// Without a path, only broadcasts and targeted events are delivered.
+		assert_eq!(fire(&mut backbone, Dispatch::Path), "");
+		assert_eq!(fire(&mut backbone, Dispatch::Broadcast), "Propagation:a Propagation:b Propagation:d Bubbling:d Bubbling:b Bubbling:a");
+		
+		backbone.location_set("/a").unwrap();
+		backbone.update_until_idle();
+		
+		assert_eq!(fire(&mut backbone, Dispatch::Path), "Propagation:a Action:a Bubbling:a");
+		assert_eq!(fire(&mut backbone, Dispatch::Target(d)), "Propagation:d Action:d Bubbling:d");
+		assert_eq!(fire(&mut backbone, Dispatch::Broadcast), "Propagation:a Propagation:b Propagation:d Action:a Bubbling:d Bubbling:b Bubbling:a");
+		
+		// The tree order is updated when nodes come and go.
+		backbone.node_new(root, "e", recorder()).unwrap();
+		backbone.node_new(root, "silent", Some(Box::new(Silent))).unwrap();
+		backbone.node_delete(c).unwrap();
+		assert_eq!(fire(&mut backbone, Dispatch::Broadcast), "Propagation:a Propagation:b Propagation:e Action:a Bubbling:e Bubbling:b Bubbling:a");
+	}
 }
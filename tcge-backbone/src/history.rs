@@ -0,0 +1,304 @@
+use std::collections::VecDeque;
+use super::{NodePath, PathError, State, Event, resolve};
+
+/// How many locations the history keeps by default, see `Backbone::history_set_capacity`.
+pub const DEFAULT_HISTORY_CAPACITY: usize = 32;
+
+/// The locations the backbone has been at, with the same semantics as the history of a browser.
+///
+/// Whenever a move ends somewhere else than it started, the location it started at is recorded
+/// and the forward history is cleared. Moving back or forward does not record anything.
+pub struct History {
+	back: VecDeque<NodePath>,
+	forward: Vec<NodePath>,
+	capacity: usize,
+	
+	/// Is a move in progress, as far as the history knows?
+	moving: bool,
+	
+	/// Where the move in progress started, if it is to be recorded.
+	origin: Option<NodePath>,
+}
+
+impl History {
+	pub fn new() -> Self {
+		Self {
+			back: VecDeque::new(),
+			forward: vec![],
+			capacity: DEFAULT_HISTORY_CAPACITY,
+			moving: false,
+			origin: None,
+		}
+	}
+	
+	pub fn can_go_back(&self) -> bool {
+		!self.back.is_empty()
+	}
+	
+	pub fn can_go_forward(&self) -> bool {
+		!self.forward.is_empty()
+	}
+	
+	/// Returns the locations `back` would move to, the most recent one last.
+	pub fn get_back(&self) -> impl Iterator<Item = &NodePath> {
+		self.back.iter()
+	}
+	
+	/// Returns the locations `forward` would move to, the next one last.
+	pub fn get_forward(&self) -> impl Iterator<Item = &NodePath> {
+		self.forward.iter()
+	}
+	
+	pub fn get_capacity(&self) -> usize {
+		self.capacity
+	}
+	
+	fn push_back(&mut self, path: NodePath) {
+		self.back.push_back(path);
+		
+		while self.back.len() > self.capacity {
+			self.back.pop_front();
+		}
+	}
+}
+
+impl Default for History {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// A direction to move trough the history in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HistoryDirection {
+	Back,
+	Forward,
+}
+
+impl std::fmt::Display for HistoryDirection {
+	fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			HistoryDirection::Back => write!(fmt, "Back"),
+			HistoryDirection::Forward => write!(fmt, "Forward"),
+		}
+	}
+}
+
+/// A passive event, fired when the history is popped, right before moving to the popped location.
+pub struct HistoryEvent {
+	pub direction: HistoryDirection,
+	
+	/// The location the backbone is leaving.
+	pub from: NodePath,
+	
+	/// The location that was popped from the history.
+	pub to: NodePath,
+}
+
+impl Event for HistoryEvent {
+	fn is_passive(&self) -> bool {
+		true
+	}
+	
+	fn get_type_name(&self) -> &'static str {
+		"HistoryEvent"
+	}
+}
+
+// Implementation details regarding the history.
+impl super::Backbone {
+	
+	pub fn history_get(&self) -> &History {
+		&self.history
+	}
+	
+	/// Sets how many locations the history keeps, dropping the oldest ones if necessary.
+	pub fn history_set_capacity(&mut self, capacity: usize) {
+		self.history.capacity = capacity;
+		
+		while self.history.back.len() > capacity {
+			self.history.back.pop_front();
+		}
+		
+		self.history.forward.truncate(capacity);
+	}
+	
+	pub fn history_clear(&mut self) {
+		self.history.back.clear();
+		self.history.forward.clear();
+	}
+	
+	/// Starts moving back to the previous location.
+	pub fn location_back(&mut self) -> Result<(), PathError> {
+		if ! self.state.can_replace() {
+			return Err(PathError::MoveInProgress)
+		}
+		
+		self.history_pop(HistoryDirection::Back)
+	}
+	
+	/// Starts moving forward to the location that was left by moving back.
+	pub fn location_forward(&mut self) -> Result<(), PathError> {
+		if ! self.state.can_replace() {
+			return Err(PathError::MoveInProgress)
+		}
+		
+		self.history_pop(HistoryDirection::Forward)
+	}
+	
+	/// Pops the history in the given direction and starts moving to the popped location.
+	///
+	/// A location that no longer resolves is dropped from the history.
+	pub(crate) fn history_pop(&mut self, direction: HistoryDirection) -> Result<(), PathError> {
+		let popped = match direction {
+			HistoryDirection::Back => self.history.back.pop_back(),
+			HistoryDirection::Forward => self.history.forward.pop(),
+		};
+		
+		let to = popped.ok_or(PathError::NoHistory)?;
+		resolve(&self.nodes, &self.path, &to)?;
+		
+		let from = self.location_get_path();
+		match direction {
+			HistoryDirection::Back => self.history.forward.push(from.clone()),
+			HistoryDirection::Forward => self.history.push_back(from.clone()),
+		}
+		
+		// Moving trough the history is not recorded.
+		self.history.moving = true;
+		self.history.origin = None;
+		self.state = State::Move(to.clone());
+		
+		self.fire_event(&mut HistoryEvent {
+			direction,
+			from,
+			to,
+		});
+		
+		Ok(())
+	}
+	
+	/// Drops the locations that no longer resolve, usually because their nodes were deleted.
+	pub(crate) fn history_prune(&mut self) {
+		let nodes = &self.nodes;
+		let path = &self.path;
+		let resolves = |location: &NodePath| resolve(nodes, path, location).is_ok();
+		
+		self.history.back.retain(|location| resolves(location));
+		self.history.forward.retain(|location| resolves(location));
+		
+		if let Some(origin) = &self.history.origin {
+			if !resolves(origin) {
+				self.history.origin = None;
+			}
+		}
+	}
+	
+	/// Called on every step of a move, to remember where it started.
+	pub(crate) fn history_move_step(&mut self) {
+		if self.history.moving {
+			return
+		}
+		
+		self.history.moving = true;
+		self.history.origin = if self.path.is_empty() {
+			None
+		} else {
+			Some(self.location_get_path())
+		};
+	}
+	
+	/// Called when a move ended, for whatever reason, to record where it started.
+	pub(crate) fn history_move_end(&mut self) {
+		self.history.moving = false;
+		
+		if let Some(origin) = self.history.origin.take() {
+			if origin != self.location_get_path() {
+				self.history.push_back(origin);
+				self.history.forward.clear();
+			}
+		}
+	}
+}
+
+impl<'a> super::Context<'a> {
+	pub fn history_get(&self) -> &History {
+		self.history
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::{Backbone, Handler, Subscriptions, Phase, Flow, Context};
+	use super::super::testing::go;
+	use super::*;
+	
+	struct Watcher {
+		popped: Vec<String>,
+	}
+	
+	impl Watcher {
+		fn on_history(&mut self, event: &mut HistoryEvent, _flow: &mut Flow, _context: &mut Context) {
+			self.popped.push(format!("{} {} -> {}", event.direction, event.from, event.to));
+		}
+	}
+	
+	impl Handler for Watcher {
+		fn subscribe(&self, subscriptions: &mut Subscriptions) {
+			subscriptions.listen(&[Phase::Propagation], 0, Watcher::on_history);
+		}
+	}
+	
+	#[test]
+	fn navigate() {
+		let mut backbone = Backbone::new();
+		let root = backbone.root_get_id();
+		backbone.set_root_node_handler(Box::new(Watcher {
+			popped: vec![],
+		}));
+		
+		for name in ["menu", "world", "settings", "credits"].iter() {
+			backbone.node_new(root, name, None).unwrap();
+		}
+		
+		go(&mut backbone, "/menu");
+		go(&mut backbone, "/world");
+		go(&mut backbone, "/settings");
+		assert_eq!(backbone.location_back(), Ok(()));
+		assert_eq!(backbone.location_back(), Err(PathError::MoveInProgress));
+		backbone.update_until_idle();
+		assert_eq!(backbone.location_get_str(), "/world");
+		
+		backbone.location_back().unwrap();
+		backbone.update_until_idle();
+		assert_eq!(backbone.location_get_str(), "/menu");
+		
+		backbone.location_forward().unwrap();
+		backbone.update_until_idle();
+		assert_eq!(backbone.location_get_str(), "/world");
+		assert!(backbone.history_get().can_go_forward());
+		
+		// A new location clears the forward history, and moving nowhere records nothing.
+		go(&mut backbone, "/credits");
+		go(&mut backbone, "/credits");
+		assert!(!backbone.history_get().can_go_forward());
+		
+		let back: Vec<String> = backbone.history_get().get_back().map(|path| path.to_string()).collect();
+		assert_eq!(back, vec!["/menu", "/world"]);
+		
+		backbone.history_set_capacity(1);
+		backbone.location_back().unwrap();
+		backbone.update_until_idle();
+		assert_eq!(backbone.location_get_str(), "/world");
+		assert_eq!(backbone.location_back(), Err(PathError::NoHistory));
+		
+		let watcher = backbone.handlers.get_mut(&root).unwrap();
+		let watcher = watcher.downcast_ref::<Watcher>().unwrap();
+		assert_eq!(watcher.popped, vec![
+			"Back /settings -> /world",
+			"Back /world -> /menu",
+			"Forward /menu -> /world",
+			"Back /credits -> /world",
+		]);
+	}
+}
@@ -15,12 +15,23 @@ mod nodes;
 mod comps;
 mod state;
 mod event;
+mod path;
+mod tree;
+mod listeners;
+mod queue;
+mod history;
+
+#[cfg(test)]
+mod testing;
 
 pub use nodes::*;
 pub use comps::*;
 pub use state::*;
 pub use event::*;
-use core::borrow::BorrowMut;
+pub use path::*;
+pub use listeners::*;
+pub use queue::*;
+pub use history::*;
 
 /// The path of a backbone.
 pub type Path = Vec<NodeId>;
@@ -48,6 +59,24 @@ pub struct Backbone {
 	/// Node event handlers.
 	handlers: Handlers,
 	
+	/// Event listeners of the handlers.
+	listeners: Listeners,
+	
+	/// The position of every node in tree order, for broadcasting events; `None` when the tree changed.
+	tree_positions: Option<FxHashMap<NodeId, usize>>,
+	
+	/// Counts the changes to the tree, so that copies of it can tell when they are stale.
+	tree_revision: usize,
+	
+	/// Reused for dispatching events.
+	dispatch_buffer: Vec<Call>,
+	
+	/// Events posted during a dispatch, fired after it.
+	queue: EventQueue,
+	
+	/// The locations the backbone has been at.
+	history: History,
+	
 	path: Path,
 	
 	path_str: String,
@@ -74,6 +103,12 @@ impl Backbone {
 			nodes,
 			comps,
 			handlers,
+			listeners: Listeners::default(),
+			tree_positions: None,
+			tree_revision: 0,
+			dispatch_buffer: vec![],
+			queue: EventQueue::new(),
+			history: History::new(),
 			path: vec![],
 			path_str: String::new(),
 			state: State::Idle,
@@ -84,13 +119,17 @@ impl Backbone {
 // Functions for location & state management
 impl Backbone {
 	
-	pub fn location_set(&mut self, path: &str) -> Result<(), ()> {
-		if self.state.can_replace() {
-			self.state = State::Move(path.to_string(), 0);
-			Ok(())
-		} else {
-			Err(())
+	/// Starts moving to the given path, which may be relative to the current location.
+	///
+	/// The path is resolved beforehand, so that an invalid path fails here instead of stopping the backbone.
+	pub fn location_set(&mut self, path: &str) -> Result<(), PathError> {
+		if ! self.state.can_replace() {
+			return Err(PathError::MoveInProgress)
 		}
+		
+		self.resolve(path)?;
+		self.state = State::Move(NodePath::parse(path));
+		Ok(())
 	}
 	
 	pub fn location_get(&self) -> &Vec<NodeId> {
@@ -110,7 +149,11 @@ impl Backbone {
 	
 	pub fn update_until_idle(&mut self) {
 		loop {
-			self.update();
+			// A stopped backbone never becomes idle again.
+			if !self.update() {
+				return
+			}
+			
 			if self.state == State::Idle {
 				return
 			}
@@ -124,39 +167,56 @@ impl Backbone {
 		}
 		
 		if self.path.is_empty() && self.state.can_replace() {
-			self.state = State::Move("/".to_string(), 0);
+			self.state = State::Move(NodePath::root());
+		}
+		
+		// Moving trough the history was requested by a handler.
+		let direction = match self.state {
+			State::Back => Some(HistoryDirection::Back),
+			State::Forward => Some(HistoryDirection::Forward),
+			_ => None,
+		};
+		
+		if let Some(direction) = direction {
+			self.state = State::Idle;
+			
+			// With nowhere to go, the backbone stays where it is.
+			if self.history_pop(direction).is_err() {
+				return true
+			}
+		}
+		
+		if let State::Move(_) = self.state {
+			self.history_move_step();
 		}
 		
-		if let State::Move(path, offset) = self.state.borrow_mut() {
+		if let State::Move(path) = &self.state {
+			// Relative moves start where the move started, so keep going with the absolute path.
+			let path = self.location_get_path().join(path);
 			
-			let step = update_path(
-				&self.nodes,
-				path.as_str(),
-				offset,
-				&self.path
-			);
+			// The destination is resolved on every step, in case the nodes changed in between.
+			let step = match resolve(&self.nodes, &self.path, &path) {
+				Ok(dst_path) => update_path(&dst_path, &self.path),
+				Err(err) => PathChange::Error(err),
+			};
 			
-			let old_path_len = self.path.len();
+			self.state = State::Move(path);
 			
 			let new_state: Option<State> = match step {
 				PathChange::ToRoot => {
-					self.path.clear();
-					self.path.push(self.root_id);
-					None
-				},
-				
-				PathChange::ToSelf => {
-					None
+					let mut vetoed = None;
+					while !self.path.is_empty() && vetoed.is_none() {
+						vetoed = self.path_pop(true);
+					}
+					vetoed.or_else(|| self.path_push(self.root_id))
 				},
 				
 				PathChange::ToSuper => {
-					self.path.pop();
-					None
+					self.path_pop(true)
 				},
 				
 				PathChange::ToNode(id) => {
-					self.path.push(id);
-					None
+					self.path_push(id)
 				},
 				
 				PathChange::Error(reason) => {
@@ -168,20 +228,93 @@ impl Backbone {
 				},
 			};
 			
-			let new_path_len = self.path.len();
-			
-			if old_path_len != new_path_len {
-				self.path_str = self.path_to_string(&self.path).expect("Failed to resolve path");
-			}
-			
 			if let Some(state) = new_state {
 				self.state = state
 			}
+			
+			match self.state {
+				State::Move(_) => (),
+				_ => self.history_move_end(),
+			}
 		}
 		
 		true
 	}
 	
+	/// Enters the given node, unless a handler vetoes it.
+	///
+	/// Once entered, the components of the node are loaded and its handler is told about it.
+	/// Returns the new state of the backbone if the move was vetoed.
+	fn path_push(&mut self, node_id: NodeId) -> Option<State> {
+		self.path.push(node_id);
+		self.path_str = self.path_to_string(&self.path).expect("Failed to resolve path");
+		
+		if let Some(state) = self.lifecycle_fire(node_id, Lifecycle::Enter) {
+			self.path.pop();
+			self.path_str = self.path_to_string(&self.path).expect("Failed to resolve path");
+			return Some(state)
+		}
+		
+		self.components_notify(node_id, true);
+		self.handler_notify(node_id, true);
+		None
+	}
+	
+	/// Leaves the current node, unless a handler vetoes it and `can_veto` is set.
+	///
+	/// Before leaving, the handler of the node is told about it and its components are unloaded.
+	/// Returns the new state of the backbone if the move was vetoed.
+	fn path_pop(&mut self, can_veto: bool) -> Option<State> {
+		if let Some(node_id) = self.location_get_node() {
+			let vetoed = self.lifecycle_fire(node_id, Lifecycle::Leave);
+			
+			if can_veto && vetoed.is_some() {
+				return vetoed
+			}
+			
+			self.handler_notify(node_id, false);
+			self.components_notify(node_id, false);
+		}
+		
+		self.path.pop();
+		self.path_str = self.path_to_string(&self.path).expect("Failed to resolve path");
+		None
+	}
+	
+	/// Loads or unloads all components of the given node.
+	fn components_notify(&mut self, node_id: NodeId, load: bool) {
+		if let Some(comps) = self.comps.get_mut(&node_id) {
+			for component in comps.values_mut() {
+				if load {
+					component.on_load();
+				} else {
+					component.on_unload();
+				}
+			}
+		}
+	}
+	
+	fn handler_notify(&mut self, node_id: NodeId, enter: bool) {
+		if let Some(handler) = self.handlers.get_mut(&node_id) {
+			let mut context = Context {
+				target: node_id,
+				current: node_id,
+				nodes: &self.nodes,
+				comps: &mut self.comps,
+				path: &self.path,
+				path_str: self.path_str.as_str(),
+				queue: &mut self.queue,
+				history: &self.history,
+			};
+			
+			if enter {
+				handler.on_enter(&mut context);
+			} else {
+				handler.on_leave(&mut context);
+			}
+		}
+	}
+	
 	pub fn get_state(&self) -> &State {
 		&self.state
 	}
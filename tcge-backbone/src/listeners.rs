@@ -0,0 +1,247 @@
+use std::any::TypeId;
+use super::{NodeId, Event, Handler, Flow, Phase, Context, FxHashMap};
+
+/// The `Listener` instances of all handlers, indexed by the event type they listen to.
+#[derive(Default)]
+pub struct Listeners {
+	/// The listeners of every handler, by the id of its node.
+	by_node: FxHashMap<NodeId, Vec<Listener>>,
+	
+	/// The typed listeners for every event type, as node and index into `by_node`, in subscription order.
+	by_type: FxHashMap<TypeId, Vec<(NodeId, usize)>>,
+	
+	/// The listeners for events of every type, as node and index into `by_node`, in subscription order.
+	fallback: Vec<(NodeId, usize)>,
+}
+
+impl Listeners {
+	/// Sets the listeners of the handler of the given node, replacing its previous ones.
+	pub(crate) fn insert(&mut self, node_id: NodeId, listeners: Vec<Listener>) {
+		self.remove(node_id);
+		
+		for (index, listener) in listeners.iter().enumerate() {
+			match listener.event {
+				Some(event) => self.by_type.entry(event).or_default().push((node_id, index)),
+				None => self.fallback.push((node_id, index)),
+			}
+		}
+		
+		self.by_node.insert(node_id, listeners);
+	}
+	
+	/// Removes the listeners of the handler of the given node.
+	pub(crate) fn remove(&mut self, node_id: NodeId) {
+		if self.by_node.remove(&node_id).is_none() {
+			return
+		}
+		
+		for subscribers in self.by_type.values_mut() {
+			subscribers.retain(|(id, _)| *id != node_id);
+		}
+		
+		self.by_type.retain(|_, subscribers| !subscribers.is_empty());
+		self.fallback.retain(|(id, _)| *id != node_id);
+	}
+	
+	/// Returns the listeners for events of the given type, typed listeners first.
+	pub(crate) fn get_subscribers(&self, event: TypeId) -> impl Iterator<Item = &(NodeId, usize)> {
+		self.by_type.get(&event)
+			.into_iter()
+			.flatten()
+			.chain(self.fallback.iter())
+	}
+	
+	pub(crate) fn get(&self, node_id: NodeId, index: usize) -> Option<&Listener> {
+		self.by_node.get(&node_id)?.get(index)
+	}
+	
+	pub(crate) fn get_mut(&mut self, node_id: NodeId, index: usize) -> Option<&mut Listener> {
+		self.by_node.get_mut(&node_id)?.get_mut(index)
+	}
+}
+
+type Callback = Box<dyn FnMut(&mut dyn Handler, &mut dyn Event, &mut Flow, &mut Context)>;
+
+/// A callback of a handler, for events of one type (or of every type) in the given phases.
+pub struct Listener {
+	/// The event type, or `None` for `Handler::on_event` and events of every type.
+	event: Option<TypeId>,
+	phases: Vec<Phase>,
+	priority: i32,
+	callback: Option<Callback>,
+}
+
+impl Listener {
+	pub fn get_priority(&self) -> i32 {
+		self.priority
+	}
+	
+	pub fn get_phases(&self) -> &[Phase] {
+		self.phases.as_slice()
+	}
+	
+	/// Returns whether this listener calls `Handler::on_event`, for events of every type.
+	pub fn is_fallback(&self) -> bool {
+		self.event.is_none()
+	}
+	
+	/// Calls the typed callback of this listener; returns `false` if it is a fallback listener.
+	pub(crate) fn call(&mut self, handler: &mut dyn Handler, event: &mut dyn Event, flow: &mut Flow, context: &mut Context) -> bool {
+		match self.callback.as_mut() {
+			Some(callback) => {
+				callback(handler, event, flow, context);
+				true
+			},
+			None => false,
+		}
+	}
+}
+
+/// The listeners a handler subscribes to, in `Handler::subscribe`.
+#[derive(Default)]
+pub struct Subscriptions {
+	listeners: Vec<Listener>,
+}
+
+impl Subscriptions {
+	pub fn new() -> Self {
+		Self::default()
+	}
+	
+	/// Subscribes the given method of the handler `H` to events of type `E`, in the given phases.
+	///
+	/// Within a phase, listeners with a higher priority are called first, wherever their node is in the path.
+	/// Listeners of equal priority are called in the order of the path, and the typed listeners
+	/// of a node before its fallback listeners.
+	pub fn listen<H, E, F>(&mut self, phases: &[Phase], priority: i32, mut callback: F) -> &mut Self
+		where H: Handler, E: Event, F: FnMut(&mut H, &mut E, &mut Flow, &mut Context) + 'static
+	{
+		let callback = move |handler: &mut dyn Handler, event: &mut dyn Event, flow: &mut Flow, context: &mut Context| {
+			if let (Some(handler), Some(event)) = (handler.downcast_mut::<H>(), event.downcast_mut::<E>()) {
+				callback(handler, event, flow, context);
+			}
+		};
+		
+		self.listeners.push(Listener {
+			event: Some(TypeId::of::<E>()),
+			phases: phases.to_vec(),
+			priority,
+			callback: Some(Box::new(callback)),
+		});
+		
+		self
+	}
+	
+	/// Subscribes `Handler::on_event` to events of every type, in the given phases.
+	///
+	/// Handlers that don't subscribe this way never have `on_event` called.
+	pub fn fallback(&mut self, phases: &[Phase], priority: i32) -> &mut Self {
+		self.listeners.push(Listener {
+			event: None,
+			phases: phases.to_vec(),
+			priority,
+			callback: None,
+		});
+		
+		self
+	}
+	
+	pub(crate) fn into_listeners(self) -> Vec<Listener> {
+		self.listeners
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::{Backbone, Wrapper};
+	use std::sync::{Arc, Mutex};
+	
+	struct Ping;
+	
+	impl Event for Ping {
+		fn get_type_name(&self) -> &'static str {
+			"Ping"
+		}
+	}
+	
+	struct Pong;
+	
+	impl Event for Pong {
+		fn get_type_name(&self) -> &'static str {
+			"Pong"
+		}
+	}
+	
+	struct Recorder {
+		name: &'static str,
+		calls: Arc<Mutex<Vec<String>>>,
+		urgent: bool,
+	}
+	
+	impl Recorder {
+		fn record(&mut self, what: &str, flow: &Flow) {
+			self.calls.lock().unwrap().push(format!("{} {} {}", self.name, what, flow.get_phase()));
+		}
+		
+		fn on_ping(&mut self, _event: &mut Ping, flow: &mut Flow, _context: &mut Context) {
+			self.record("ping", flow);
+		}
+		
+		fn on_urgent_ping(&mut self, _event: &mut Ping, flow: &mut Flow, _context: &mut Context) {
+			self.record("urgent-ping", flow);
+			flow.stop_bubbling();
+		}
+	}
+	
+	impl Handler for Recorder {
+		fn on_event(&mut self, event: &mut Wrapper, _context: &mut Context) {
+			if event.downcast::<Pong>().is_some() {
+				self.record("pong", event);
+			}
+		}
+		
+		fn subscribe(&self, subscriptions: &mut Subscriptions) {
+			subscriptions
+				.listen(&[Phase::Propagation, Phase::Action], 0, Recorder::on_ping)
+				.fallback(&[Phase::Propagation, Phase::Action, Phase::Bubbling], 0);
+			
+			if self.urgent {
+				subscriptions.listen(&[Phase::Bubbling], 10, Recorder::on_urgent_ping);
+			}
+		}
+	}
+	
+	#[test]
+	fn dispatch() {
+		let calls = Arc::new(Mutex::new(vec![]));
+		let recorder = |name, urgent| Box::new(Recorder {
+			name,
+			calls: calls.clone(),
+			urgent,
+		});
+		
+		let mut backbone = Backbone::new();
+		let root = backbone.root_get_id();
+		backbone.set_root_node_handler(recorder("root", false));
+		let child = backbone.node_new(root, "child", Some(recorder("child", true))).unwrap();
+		backbone.node_new(child, "leaf", Some(recorder("leaf", false))).unwrap();
+		
+		backbone.location_set("/child/leaf").unwrap();
+		backbone.update_until_idle();
+		calls.lock().unwrap().clear();
+		
+		backbone.fire_event(&mut Ping);
+		assert_eq!(*calls.lock().unwrap(), vec![
+			"root ping Propagation",
+			"child ping Propagation",
+			"leaf ping Propagation",
+			"leaf ping Action",
+			"child urgent-ping Bubbling",
+		]);
+		
+		calls.lock().unwrap().clear();
+		backbone.fire_event(&mut Pong);
+		assert_eq!(calls.lock().unwrap().len(), 7);
+	}
+}
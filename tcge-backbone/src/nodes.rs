@@ -1,4 +1,5 @@
 use super::Handler;
+use super::State;
 
 /// Collection type for `Node` instances.
 pub type Nodes = super::FxHashMap<NodeId, Node>;
@@ -29,19 +30,131 @@ impl super::Backbone {
 		
 		// Add node to tree
 		self.nodes.insert(node_id, node);
+		self.tree_positions = None;
+		self.tree_revision += 1;
 		
 		// Has handler? Add handler.
 		if let Some(handler) = handler {
-			self.handlers.insert(node_id, handler);
+			self.handler_insert(node_id, handler);
 		}
 		
 		Ok(node_id)
 	}
 	
+	/// Removes the given node and all of its descendants.
+	///
+	/// The components of the removed nodes are detached and their handlers dropped.
+	/// If the current path runs trough a removed node, the removed nodes are left first,
+	/// so that the backbone ends up at the parent of the given node.
+	///
+	/// The root node can not be removed.
+	pub fn node_delete(&mut self, node_id: NodeId) -> Result<(), NodeError> {
+		if node_id == self.root_id {
+			return Err(NodeError::IsRoot)
+		}
+		
+		if ! self.nodes.contains_key(&node_id) {
+			return Err(NodeError::UnknownNode(node_id))
+		}
+		
+		let subtree = self.node_get_subtree(node_id);
+		
+		// Leaving the removed nodes unloads their components, and can't be vetoed.
+		let depth = self.path.iter().position(|id| *id == node_id);
+		if let Some(depth) = depth {
+			while self.path.len() > depth {
+				self.path_pop(false);
+			}
+		}
+		
+		// Children go before their parents.
+		for id in subtree.iter().rev() {
+			if let Some(comps) = self.comps.remove(id) {
+				for (_, mut component) in comps {
+					component.on_detachment(*id);
+				}
+			}
+			
+			self.handlers.remove(id);
+			self.listeners.remove(*id);
+			self.nodes.remove(id);
+		}
+		
+		self.tree_positions = None;
+		self.tree_revision += 1;
+		self.history_prune();
+		
+		// A move in progress may lead into the removed nodes,
+		// or may not have started yet and be relative to a location that is gone.
+		if let State::Move(path) = &self.state {
+			let stale = depth.is_some() && ! path.is_absolute();
+			if stale || super::resolve(&self.nodes, &self.path, path).is_err() {
+				self.state = State::Idle;
+				self.history_move_end();
+			}
+		}
+		
+		Ok(())
+	}
+	
+	/// Removes all children of the given node, keeping the node itself.
+	pub fn node_delete_children(&mut self, node_id: NodeId) -> Result<(), NodeError> {
+		if ! self.nodes.contains_key(&node_id) {
+			return Err(NodeError::UnknownNode(node_id))
+		}
+		
+		for child in self.node_get_children(node_id) {
+			self.node_delete(child)?;
+		}
+		
+		Ok(())
+	}
+	
+	pub fn node_get(&self, node_id: NodeId) -> Option<&Node> {
+		self.nodes.get(&node_id)
+	}
+	
+	/// Returns the id of the node at the given path, which may be relative to the current location.
+	pub fn node_get_by_path(&self, path: &str) -> Result<NodeId, super::PathError> {
+		let resolved = self.resolve(path)?;
+		Ok(*resolved.last().expect("resolved path always contains the root"))
+	}
+	
+	/// Returns the ids of the direct children of the given node, in order of creation.
+	pub fn node_get_children(&self, node_id: NodeId) -> Vec<NodeId> {
+		get_children(&self.nodes, node_id)
+	}
+	
+	/// Returns the ids of the given node and all of its descendants, parents before children.
+	fn node_get_subtree(&self, node_id: NodeId) -> Vec<NodeId> {
+		let children = get_children_map(&self.nodes);
+		let mut subtree = vec![node_id];
+		let mut index = 0;
+		
+		while index < subtree.len() {
+			if let Some(children) = children.get(&subtree[index]) {
+				subtree.extend(children);
+			}
+			index += 1;
+		}
+		
+		subtree
+	}
+	
+	/// Returns all nodes of the tree.
+	pub fn nodes_get(&self) -> &Nodes {
+		&self.nodes
+	}
+	
+	/// Returns a number that changes whenever a node is added or removed.
+	pub fn tree_revision(&self) -> usize {
+		self.tree_revision
+	}
+	
 	pub fn path_to_string(&self, path: &[NodeId]) -> Result<String, ()> {
 		let mut path_str = String::new();
 		
-		for item in &path[1..] {
+		for item in path.iter().skip(1) {
 			let node = self.nodes.get(item);
 			
 			if let Some(node) = node {
@@ -60,6 +173,67 @@ impl super::Backbone {
 	}
 }
 
+/// Returns the ids of the direct children of the given node, in order of creation.
+pub(crate) fn get_children(nodes: &Nodes, node_id: NodeId) -> Vec<NodeId> {
+	let mut children: Vec<NodeId> = nodes.values()
+		.filter(|node| node.parent == node_id && node.id != node_id)
+		.map(|node| node.id)
+		.collect();
+	
+	children.sort_by_key(|id| id.inner);
+	children
+}
+
+/// Returns the direct children of every node that has any, in order of creation.
+///
+/// Building this once is cheaper than calling `get_children` for many nodes.
+pub(crate) fn get_children_map(nodes: &Nodes) -> super::FxHashMap<NodeId, Vec<NodeId>> {
+	let mut map: super::FxHashMap<NodeId, Vec<NodeId>> = super::FxHashMap::default();
+	
+	for node in nodes.values() {
+		if node.parent != node.id {
+			map.entry(node.parent).or_default().push(node.id);
+		}
+	}
+	
+	for children in map.values_mut() {
+		children.sort_by_key(|id| id.inner);
+	}
+	
+	map
+}
+
+/// Returns the given node and all nodes below it, parents before their children.
+pub(crate) fn get_tree_order(nodes: &Nodes, node_id: NodeId) -> Vec<NodeId> {
+	let children = get_children_map(nodes);
+	let mut order = vec![];
+	let mut stack = vec![node_id];
+	
+	while let Some(id) = stack.pop() {
+		order.push(id);
+		
+		if let Some(children) = children.get(&id) {
+			stack.extend(children.iter().rev());
+		}
+	}
+	
+	order
+}
+
+/// Returns the path from the root to the given node, if the node exists.
+pub(crate) fn get_ancestry(nodes: &Nodes, node_id: NodeId) -> Option<Vec<NodeId>> {
+	let mut ancestry = vec![node_id];
+	let mut node = nodes.get(&node_id)?;
+	
+	while node.parent != node.id {
+		ancestry.push(node.parent);
+		node = nodes.get(&node.parent)?;
+	}
+	
+	ancestry.reverse();
+	Some(ancestry)
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 
 type InternalNodeId = usize;
@@ -127,34 +301,95 @@ impl Node {
 	}
 }
 
+////////////////////////////////////////////////////////////////////////////////
 
+/// Errors that may occur when changing the node tree.
+#[derive(Clone, PartialEq, Debug)]
+pub enum NodeError {
+	/// The given node does not exist.
+	UnknownNode(NodeId),
+	
+	/// The root node can not be removed.
+	IsRoot,
+}
 
+impl std::fmt::Display for NodeError {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+		match self {
+			NodeError::UnknownNode(node) => write!(f, "There is no node {}", node),
+			NodeError::IsRoot => write!(f, "The root node can not be removed"),
+		}
+	}
+}
 
-
-
-
-
-
-
-
-
-
-
-
-
-// TODO: Re-Implement the node "handlers".
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::Backbone;
+	use super::super::testing::{Log, Logger, go};
+	
+	#[test]
+	fn delete() {
+		let log = Log::default();
+		let mut backbone = Backbone::new();
+		let root = backbone.root_get_id();
+		let a = backbone.node_new(root, "a", None).unwrap();
+		let b = backbone.node_new(a, "b", None).unwrap();
+		let c = backbone.node_new(b, "c", None).unwrap();
+		let d = backbone.node_new(root, "d", None).unwrap();
+		
+		for (id, name) in [(a, "a"), (b, "b"), (c, "c")].iter() {
+			backbone.node_component_attach(*id, Logger::new(name, &log));
+		}
+		
+		go(&mut backbone, "/d");
+		go(&mut backbone, "/a/b/c");
+		go(&mut backbone, "/a/b");
+		go(&mut backbone, "/a/b/c");
+		log.borrow_mut().clear();
+		
+		assert_eq!(backbone.node_delete(root), Err(NodeError::IsRoot));
+		
+		// The path runs trough the deleted node, so the removed nodes are left first.
+		let revision = backbone.tree_revision();
+		backbone.node_delete(b).unwrap();
+		assert_ne!(backbone.tree_revision(), revision);
+		assert_eq!(*log.borrow(), vec!["unload c", "unload b", "detach c", "detach b"]);
+		assert_eq!(backbone.location_get_str(), "/a");
+		assert!(backbone.node_get(b).is_none());
+		assert!(backbone.node_get(c).is_none());
+		assert!(backbone.node_get_children(a).is_empty());
+		assert!(backbone.node_component_names(c).is_empty());
+		assert_eq!(backbone.node_delete(b), Err(NodeError::UnknownNode(b)));
+		
+		// The history no longer leads into the deleted nodes.
+		let back: Vec<String> = backbone.history_get().get_back().map(|path| path.to_string()).collect();
+		assert_eq!(back, vec!["/d"]);
+		
+		// A move into the deleted nodes is cancelled.
+		backbone.location_set("/d").unwrap();
+		backbone.node_delete(d).unwrap();
+		assert!(*backbone.get_state() == State::Idle);
+		
+		log.borrow_mut().clear();
+		backbone.node_delete_children(root).unwrap();
+		assert_eq!(*log.borrow(), vec!["unload a", "detach a"]);
+		assert_eq!(backbone.location_get_str(), "/");
+		assert!(backbone.node_get_children(root).is_empty());
+		assert_eq!(backbone.nodes.len(), 1);
+	}
+	
+	#[test]
+	fn tree_order() {
+		let mut backbone = Backbone::new();
+		let root = backbone.root_get_id();
+		let a = backbone.node_new(root, "a", None).unwrap();
+		let b = backbone.node_new(root, "b", None).unwrap();
+		let c = backbone.node_new(a, "c", None).unwrap();
+		let d = backbone.node_new(b, "d", None).unwrap();
+		let e = backbone.node_new(a, "e", None).unwrap();
+		
+		assert_eq!(get_tree_order(&backbone.nodes, root), vec![root, a, c, e, b, d]);
+		assert_eq!(backbone.node_get_subtree(a), vec![a, c, e]);
+	}
+}
@@ -0,0 +1,324 @@
+use super::{NodeId, Nodes};
+
+// Implementation details regarding paths.
+impl super::Backbone {
+	
+	/// Resolves the given path to the ids of the nodes it runs trough, without moving.
+	///
+	/// Relative paths are resolved against the current location.
+	pub fn resolve(&self, path: &str) -> Result<Vec<NodeId>, PathError> {
+		resolve(&self.nodes, &self.path, &NodePath::parse(path))
+	}
+	
+	/// Returns the current location as absolute path.
+	pub fn location_get_path(&self) -> NodePath {
+		NodePath::root().join(&NodePath::parse(&self.path_str))
+	}
+}
+
+impl<'a> super::Context<'a> {
+	/// Resolves the given path to the ids of the nodes it runs trough.
+	///
+	/// Relative paths are resolved against the location of the backbone.
+	pub fn resolve(&self, path: &str) -> Result<Vec<NodeId>, PathError> {
+		resolve(self.nodes, self.path, &NodePath::parse(path))
+	}
+}
+
+/// Resolves the given path against the location `from`, returning the ids of the nodes it runs trough.
+///
+/// An empty location is treated as the root node. Moving above the root node stays at the root node.
+pub fn resolve(nodes: &Nodes, from: &[NodeId], path: &NodePath) -> Result<Vec<NodeId>, PathError> {
+	let root_id = nodes.values()
+		.find(|node| node.get_parent_id() == node.get_id())
+		.expect("the backbone always has a root node")
+		.get_id();
+	
+	let mut resolved = if path.is_absolute() || from.is_empty() {
+		vec![root_id]
+	} else {
+		from.to_vec()
+	};
+	
+	for segment in path.get_segments() {
+		match segment {
+			Segment::Parent => {
+				if resolved.len() > 1 {
+					resolved.pop();
+				}
+			},
+			
+			Segment::Name(name) => {
+				let current = *resolved.last().expect("resolved path always contains the root");
+				
+				let mut found = nodes.values().filter(|node| {
+					node.get_parent_id() == current
+						&& node.get_id() != current
+						&& node.get_name() == name
+				});
+				
+				let next = match (found.next(), found.next()) {
+					(Some(next), None) => next.get_id(),
+					(None, _) => return Err(PathError::UnknownNode {
+						path: path_to_string(nodes, &resolved),
+						name: name.clone(),
+					}),
+					(Some(_), Some(_)) => return Err(PathError::AmbiguousName {
+						path: path_to_string(nodes, &resolved),
+						name: name.clone(),
+					}),
+				};
+				
+				resolved.push(next);
+			},
+		}
+	}
+	
+	Ok(resolved)
+}
+
+fn path_to_string(nodes: &Nodes, path: &[NodeId]) -> String {
+	let names: Vec<&str> = path.iter()
+		.skip(1)
+		.filter_map(|id| nodes.get(id))
+		.map(|node| node.get_name())
+		.collect();
+	
+	format!("/{}", names.join("/"))
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A segment of a `NodePath`.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Segment {
+	/// `..`
+	Parent,
+	
+	/// `NAME`
+	Name(String),
+}
+
+/// A normalized path of nodes in the backbone, like `/playground` or `../menu`.
+///
+/// Empty segments and `.` are dropped while parsing, and every `..` cancels out
+/// the name before it. Only relative paths can start with `..`, as the root has no parent.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct NodePath {
+	absolute: bool,
+	segments: Vec<Segment>,
+}
+
+impl NodePath {
+	/// Parses and normalizes the given path.
+	pub fn parse(path: &str) -> Self {
+		let absolute = path.starts_with('/');
+		let mut new = Self {
+			absolute,
+			segments: vec![],
+		};
+		
+		for segment in path.split('/') {
+			new.push(segment);
+		}
+		
+		new
+	}
+	
+	/// The path of the root node.
+	pub fn root() -> Self {
+		Self {
+			absolute: true,
+			segments: vec![],
+		}
+	}
+	
+	pub fn is_absolute(&self) -> bool {
+		self.absolute
+	}
+	
+	pub fn get_segments(&self) -> &[Segment] {
+		self.segments.as_slice()
+	}
+	
+	/// Returns the given path relative to this one; absolute paths are returned as they are.
+	pub fn join(&self, other: &NodePath) -> NodePath {
+		if other.absolute {
+			return other.clone()
+		}
+		
+		let mut joined = self.clone();
+		for segment in other.segments.iter() {
+			match segment {
+				Segment::Parent => joined.push(".."),
+				Segment::Name(name) => joined.push(name),
+			}
+		}
+		
+		joined
+	}
+	
+	fn push(&mut self, segment: &str) {
+		match segment {
+			"" | "." => (),
+			".." => match self.segments.last() {
+				Some(Segment::Name(_)) => {
+					self.segments.pop();
+				},
+				_ if self.absolute => (),
+				_ => self.segments.push(Segment::Parent),
+			},
+			name => self.segments.push(Segment::Name(name.to_string())),
+		}
+	}
+}
+
+impl From<&str> for NodePath {
+	fn from(path: &str) -> Self {
+		NodePath::parse(path)
+	}
+}
+
+impl std::fmt::Display for NodePath {
+	fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+		if self.segments.is_empty() {
+			return write!(fmt, "{}", if self.absolute {"/"} else {"."})
+		}
+		
+		for (index, segment) in self.segments.iter().enumerate() {
+			if index > 0 || self.absolute {
+				write!(fmt, "/")?;
+			}
+			
+			match segment {
+				Segment::Parent => write!(fmt, "..")?,
+				Segment::Name(name) => write!(fmt, "{}", name)?,
+			}
+		}
+		
+		Ok(())
+	}
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Errors that may occur when resolving paths or changing the location.
+#[derive(Clone, PartialEq, Debug)]
+pub enum PathError {
+	/// There is no node with the given name below the given path.
+	UnknownNode {
+		path: String,
+		name: String,
+	},
+	
+	/// There is more than one node with the given name below the given path.
+	AmbiguousName {
+		path: String,
+		name: String,
+	},
+	
+	/// The backbone is already moving or stopped, and can't start another move.
+	MoveInProgress,
+	
+	/// There is no location in the history to move back or forward to.
+	NoHistory,
+}
+
+impl std::fmt::Display for PathError {
+	fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			PathError::UnknownNode {path, name} => write!(fmt, "Could not find node '{}' in '{}'", name, path),
+			PathError::AmbiguousName {path, name} => write!(fmt, "There are multiple nodes named '{}' in '{}'", name, path),
+			PathError::MoveInProgress => write!(fmt, "Another move is already in progress"),
+			PathError::NoHistory => write!(fmt, "There is no location to go to in the history"),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::{Backbone, State};
+	
+	#[test]
+	fn normalize() {
+		assert_eq!(NodePath::parse("/a/./b//c/../d/").to_string(), "/a/b/d");
+		assert_eq!(NodePath::parse("/a/../..").to_string(), "/");
+		assert_eq!(NodePath::parse("").to_string(), ".");
+		assert_eq!(NodePath::parse("./").to_string(), ".");
+		assert_eq!(NodePath::parse("a/..").to_string(), ".");
+		assert_eq!(NodePath::parse("../../a/../b").to_string(), "../../b");
+		assert_eq!(NodePath::parse("x/../../b").to_string(), "../b");
+		
+		let base = NodePath::parse("/a/b");
+		assert_eq!(base.join(&"../c".into()).to_string(), "/a/c");
+		assert_eq!(base.join(&"../../..".into()).to_string(), "/");
+		assert_eq!(base.join(&"/d".into()).to_string(), "/d");
+		assert_eq!(NodePath::parse("..").join(&"../a".into()).to_string(), "../../a");
+	}
+	
+	#[test]
+	fn resolve() {
+		let mut backbone = Backbone::new();
+		let root = backbone.root_get_id();
+		let a = backbone.node_new(root, "a", None).unwrap();
+		let b = backbone.node_new(a, "b", None).unwrap();
+		let c = backbone.node_new(root, "c", None).unwrap();
+		backbone.node_new(c, "twin", None).unwrap();
+		backbone.node_new(c, "twin", None).unwrap();
+		
+		// Without a location, relative paths start at the root.
+		assert_eq!(backbone.resolve("a/b"), Ok(vec![root, a, b]));
+		assert_eq!(backbone.resolve("/a/b/../../c"), Ok(vec![root, c]));
+		assert_eq!(backbone.resolve("/.."), Ok(vec![root]));
+		
+		assert_eq!(backbone.resolve("/a/x"), Err(PathError::UnknownNode {
+			path: "/a".to_string(),
+			name: "x".to_string(),
+		}));
+		assert_eq!(backbone.resolve("/c/twin"), Err(PathError::AmbiguousName {
+			path: "/c".to_string(),
+			name: "twin".to_string(),
+		}));
+		
+		// Resolving does not move the backbone.
+		assert!(backbone.location_get().is_empty());
+		
+		backbone.location_set("/a/b").unwrap();
+		backbone.update_until_idle();
+		assert_eq!(backbone.location_get(), &vec![root, a, b]);
+		assert_eq!(backbone.resolve("../../c"), Ok(vec![root, c]));
+		assert_eq!(backbone.resolve("."), Ok(vec![root, a, b]));
+	}
+	
+	#[test]
+	fn location_set() {
+		let mut backbone = Backbone::new();
+		let root = backbone.root_get_id();
+		let a = backbone.node_new(root, "a", None).unwrap();
+		let b = backbone.node_new(a, "b", None).unwrap();
+		let c = backbone.node_new(root, "c", None).unwrap();
+		
+		assert!(backbone.location_set("/a/nope").is_err());
+		assert!(backbone.get_state() == &State::Idle);
+		
+		backbone.location_set("/a/b").unwrap();
+		assert_eq!(backbone.location_set("/c"), Err(PathError::MoveInProgress));
+		backbone.update_until_idle();
+		assert_eq!(backbone.location_get_str(), "/a/b");
+		
+		// Relative moves with `..` in the middle and at the end.
+		backbone.location_set("../../c/../a/b/..").unwrap();
+		backbone.update_until_idle();
+		assert_eq!(backbone.location_get(), &vec![root, a]);
+		
+		backbone.location_set("b").unwrap();
+		backbone.update_until_idle();
+		assert_eq!(backbone.location_get(), &vec![root, a, b]);
+		assert_eq!(backbone.location_get_path(), NodePath::parse("/a/b"));
+		
+		backbone.location_set("/c").unwrap();
+		backbone.update_until_idle();
+		assert_eq!(backbone.location_get(), &vec![root, c]);
+	}
+}
@@ -0,0 +1,162 @@
+use std::collections::VecDeque;
+use super::{Event, State};
+
+/// How many queued events are fired after a dispatch, unless changed with `Backbone::event_queue_set_limit`.
+pub const DEFAULT_DRAIN_LIMIT: usize = 64;
+
+/// Events that were posted during a dispatch, waiting to be fired after it.
+pub struct EventQueue {
+	events: VecDeque<Box<dyn Event>>,
+	limit: usize,
+}
+
+impl EventQueue {
+	pub fn new() -> Self {
+		Self {
+			events: VecDeque::new(),
+			limit: DEFAULT_DRAIN_LIMIT,
+		}
+	}
+	
+	pub fn post(&mut self, event: Box<dyn Event>) {
+		self.events.push_back(event);
+	}
+	
+	pub fn len(&self) -> usize {
+		self.events.len()
+	}
+	
+	pub fn is_empty(&self) -> bool {
+		self.events.is_empty()
+	}
+}
+
+impl Default for EventQueue {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+// Implementation details regarding the event queue.
+impl super::Backbone {
+	
+	/// Queues the given event, to be fired after the next dispatch.
+	pub fn event_post<E: Event>(&mut self, event: E) {
+		self.queue.post(Box::new(event));
+	}
+	
+	/// Returns the number of events waiting in the queue.
+	pub fn event_queue_len(&self) -> usize {
+		self.queue.len()
+	}
+	
+	pub fn event_queue_get_limit(&self) -> usize {
+		self.queue.limit
+	}
+	
+	/// Sets how many queued events are fired at most per drain.
+	pub fn event_queue_set_limit(&mut self, limit: usize) {
+		self.queue.limit = limit;
+	}
+	
+	/// Fires the queued events in the order they were posted, returning how many were fired.
+	///
+	/// Events posted while draining are queued behind the others.
+	/// Draining stops at the drain limit, or at the first event that is not passive
+	/// while the backbone is busy; the remaining events wait for the next drain.
+	pub fn event_queue_drain(&mut self) -> usize {
+		let mut fired = 0;
+		
+		while fired < self.queue.limit {
+			let can_fire = match self.queue.events.front() {
+				Some(event) => event.is_passive() || self.state == State::Idle,
+				None => false,
+			};
+			
+			if !can_fire {
+				break;
+			}
+			
+			if let Some(mut event) = self.queue.events.pop_front() {
+				self.fire_event_now(&mut *event);
+				fired += 1;
+			}
+		}
+		
+		fired
+	}
+}
+
+impl<'a> super::Context<'a> {
+	/// Queues the given event, to be fired once the current dispatch is done.
+	pub fn event_post<E: Event>(&mut self, event: E) {
+		self.queue.post(Box::new(event));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::{Backbone, Handler, Subscriptions, Phase, Flow, Context};
+	use super::*;
+	
+	struct Ping;
+	
+	impl Event for Ping {
+		fn get_type_name(&self) -> &'static str {
+			"Ping"
+		}
+	}
+	
+	struct Echo(usize);
+	
+	impl Event for Echo {
+		fn get_type_name(&self) -> &'static str {
+			"Echo"
+		}
+	}
+	
+	struct Poster {
+		echoes: Vec<usize>,
+	}
+	
+	impl Poster {
+		fn on_ping(&mut self, _event: &mut Ping, _flow: &mut Flow, context: &mut Context) {
+			context.event_post(Echo(0));
+			context.event_post(Echo(100));
+		}
+		
+		fn on_echo(&mut self, event: &mut Echo, _flow: &mut Flow, context: &mut Context) {
+			self.echoes.push(event.0);
+			context.event_post(Echo(event.0 + 1));
+		}
+	}
+	
+	impl Handler for Poster {
+		fn subscribe(&self, subscriptions: &mut Subscriptions) {
+			subscriptions
+				.listen(&[Phase::Action], 0, Poster::on_ping)
+				.listen(&[Phase::Action], 0, Poster::on_echo);
+		}
+	}
+	
+	#[test]
+	fn drain() {
+		let mut backbone = Backbone::new();
+		backbone.set_root_node_handler(Box::new(Poster {
+			echoes: vec![],
+		}));
+		backbone.update_until_idle();
+		backbone.event_queue_set_limit(5);
+		
+		backbone.fire_event(&mut Ping);
+		assert_eq!(backbone.event_queue_len(), 2);
+		
+		backbone.event_queue_set_limit(2);
+		assert_eq!(backbone.event_queue_drain(), 2);
+		assert_eq!(backbone.event_queue_len(), 2);
+		
+		let poster = backbone.handlers.get_mut(&backbone.root_get_id()).unwrap();
+		let poster = poster.downcast_ref::<Poster>().unwrap();
+		assert_eq!(poster.echoes, vec![0, 100, 1, 101, 2, 102, 3]);
+	}
+}
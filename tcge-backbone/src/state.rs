@@ -1,10 +1,10 @@
-use super::{NodeId, Nodes, Node, Event};
+use super::{NodeId, NodePath, PathError, Event};
 
 /// This function is called when the backbone attempts to change its path.
+///
+/// Returns the next step to take from `src_path` towards `dst_path`, both being resolved paths.
 pub fn update_path(
-	nodes: &Nodes,
-	dst_path: &str,
-	dst_offset: &mut usize,
+	dst_path: &[NodeId],
 	src_path: &[NodeId]
 ) -> PathChange {
 	// If we are not within the backbone, move to the root node (#0).
@@ -12,85 +12,21 @@ pub fn update_path(
 		return PathChange::ToRoot
 	}
 	
-	// Parsing of starting location only happens when `offset = 0`
-	if *dst_offset == 0 {
-		if dst_path.starts_with('/') {
-			// Bubble until you hit the root
-			if src_path.len() > 1 {
-				return PathChange::ToSuper;
-			}
-			
-			*dst_offset += 1;
-		}
-		
-		if dst_path.starts_with("./") {
-			*dst_offset += 2;
-			return PathChange::ToSelf
-		}
-		
-		if dst_path.starts_with("../") {
-			*dst_offset += 3;
-			return PathChange::ToSuper
-		}
-	}
-	
-	// Slice away everything before the offset
-	let mut path = dst_path.split_at(*dst_offset).1;
-	
-	// Have we already reached the end?
-	if path.is_empty() {
-		return PathChange::End;
-	}
-	
-	// Slice away unnecessary slashes
-	while path.starts_with('/') {
-		*dst_offset += 1;
-		path = &path[1..];
-	}
+	let common = src_path.iter()
+		.zip(dst_path.iter())
+		.take_while(|(src, dst)| src == dst)
+		.count();
 	
-	if path.starts_with("./") {
-		*dst_offset += 2;
-		return PathChange::ToSelf
-	}
-	
-	if path.starts_with("../") {
-		*dst_offset += 3;
+	// Bubble until we are on the way to the destination...
+	if src_path.len() > common {
 		return PathChange::ToSuper
 	}
 	
-	let current = src_path.last().expect("src_path should not be empty due to the first guard-clause");
-	let current = nodes.get(current);
-	
-	let current = match current {
-		Some(x) => x,
-		None => return PathChange::Error("Could not resolve current.".to_string()),
-	};
-	
-	let end = path.find('/')
-		.unwrap_or(path.len());
-	
-	let name = &path[..end];
-	
-	let mut next: Option<&Node> = None;
-	for (_, node) in nodes.iter() {
-		if node.get_name() != name {
-			continue;
-		}
-		
-		if node.get_parent_id() != current.get_id() {
-			continue;
-		}
-		
-		next = Some(node);
+	// ...then descend towards it.
+	match dst_path.get(common) {
+		Some(next) => PathChange::ToNode(*next),
+		None => PathChange::End,
 	}
-	
-	let next = match next {
-		None => return PathChange::Error(format!("Could not find node: {}", name)),
-		Some(x) => x,
-	};
-	
-	*dst_offset += end;
-	PathChange::ToNode(next.get_id())
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -100,9 +36,6 @@ pub enum PathChange {
 	/// `/`
 	ToRoot,
 	
-	/// `./`
-	ToSelf,
-	
 	/// `../`
 	ToSuper,
 	
@@ -110,7 +43,7 @@ pub enum PathChange {
 	ToNode(NodeId),
 	
 	/// Completion: Failure
-	Error(String),
+	Error(PathError),
 	
 	/// Completion: Success
 	End
@@ -120,7 +53,6 @@ impl std::fmt::Display for PathChange {
 	fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
 		match self {
 			PathChange::ToRoot => write!(fmt, "/"),
-			PathChange::ToSelf => write!(fmt, "./"),
 			PathChange::ToSuper => write!(fmt, "../"),
 			PathChange::ToNode(x) => write!(fmt, "/{}", x),
 			PathChange::Error(x) => write!(fmt, "!Err({})", x),
@@ -135,7 +67,16 @@ impl std::fmt::Display for PathChange {
 /// The state of the backbones location.
 pub enum State {
 	Idle,
-	Move(String, usize),
+	
+	/// Moving towards the given path, which is relative to where the move started.
+	Move(NodePath),
+	
+	/// Moving back to the previous location in the history, see `Backbone::location_back`.
+	Back,
+	
+	/// Moving forward to the next location in the history, see `Backbone::location_forward`.
+	Forward,
+	
 	Stop(Option<String>),
 	Fire(Box<dyn Event>),
 }
@@ -144,7 +85,9 @@ impl State {
 	pub fn can_replace(&self) -> bool {
 		match self {
 			State::Idle => true,
-			State::Move(_, _) => false,
+			State::Move(_) => false,
+			State::Back => false,
+			State::Forward => false,
 			State::Stop(_) => false,
 			State::Fire(_) => false,
 		}
@@ -161,7 +104,9 @@ impl std::fmt::Display for State {
 	fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
 		match self {
 			State::Idle => write!(fmt, "Idle"),
-			State::Move(path, offset) => write!(fmt, "Move({}, {})", path, offset),
+			State::Move(path) => write!(fmt, "Move({})", path),
+			State::Back => write!(fmt, "Back"),
+			State::Forward => write!(fmt, "Forward"),
 			State::Fire(event) => write!(fmt, "Fire({})", event.get_type_name()),
 			State::Stop(_reason) => write!(fmt, "Stop(?)"),
 		}
@@ -0,0 +1,55 @@
+//! Fixtures shared by the tests of the backbone.
+
+use super::{Backbone, Component, NodeId};
+use std::rc::Rc;
+use std::cell::RefCell;
+
+/// The entries written by `Logger`s, oldest first.
+pub type Log = Rc<RefCell<Vec<String>>>;
+
+/// A component that logs its lifecycle as `<what> <name>`, like `unload a`.
+pub struct Logger {
+	name: &'static str,
+	log: Log,
+}
+
+impl Logger {
+	pub fn new(name: &'static str, log: &Log) -> Self {
+		Self {
+			name,
+			log: log.clone(),
+		}
+	}
+	
+	fn record(&self, what: &str) {
+		self.log.borrow_mut().push(format!("{} {}", what, self.name));
+	}
+}
+
+impl Component for Logger {
+	fn get_type_name(&self) -> &'static str {
+		"Logger"
+	}
+	
+	fn on_attachment(&mut self, _node_id: NodeId) {
+		self.record("attach");
+	}
+	
+	fn on_detachment(&mut self, _node_id: NodeId) {
+		self.record("detach");
+	}
+	
+	fn on_load(&mut self) {
+		self.record("load");
+	}
+	
+	fn on_unload(&mut self) {
+		self.record("unload");
+	}
+}
+
+/// Moves the backbone to the given path and waits until it arrived.
+pub fn go(backbone: &mut Backbone, path: &str) {
+	backbone.location_set(path).unwrap();
+	backbone.update_until_idle();
+}
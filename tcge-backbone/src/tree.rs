@@ -0,0 +1,144 @@
+use std::fmt::Write;
+use super::{NodeId, Nodes, Comps};
+use super::nodes::get_children;
+use super::comps::get_component_names;
+
+// Functions for inspecting the tree of nodes.
+impl super::Backbone {
+	
+	/// Returns the subtree at the given node as indented text, one node per line.
+	///
+	/// Every line shows the name, id and component types of a node,
+	/// with the nodes of the current path marked by a `*`.
+	pub fn tree_dump(&self, node_id: NodeId) -> String {
+		let mut out = String::new();
+		dump_text(&self.nodes, &self.comps, &self.path, node_id, 0, &mut out);
+		out
+	}
+	
+	/// Returns the subtree at the given node as JSON object.
+	///
+	/// Every node is written as `{"id", "name", "entered", "components", "children"}`.
+	pub fn tree_dump_json(&self, node_id: NodeId) -> String {
+		let mut out = String::new();
+		dump_json(&self.nodes, &self.comps, &self.path, node_id, &mut out);
+		out
+	}
+}
+
+impl<'a> super::Context<'a> {
+	/// Returns the subtree at the given node as indented text, like `Backbone::tree_dump`.
+	pub fn tree_dump(&self, node_id: NodeId) -> String {
+		let mut out = String::new();
+		dump_text(self.nodes, self.comps, self.path, node_id, 0, &mut out);
+		out
+	}
+	
+	/// Returns the subtree at the given node as JSON object, like `Backbone::tree_dump_json`.
+	pub fn tree_dump_json(&self, node_id: NodeId) -> String {
+		let mut out = String::new();
+		dump_json(self.nodes, self.comps, self.path, node_id, &mut out);
+		out
+	}
+}
+
+fn dump_text(nodes: &Nodes, comps: &Comps, path: &[NodeId], node_id: NodeId, depth: usize, out: &mut String) {
+	let node = match nodes.get(&node_id) {
+		Some(node) => node,
+		None => return,
+	};
+	
+	let name = if node.get_parent_id() == node_id {"/"} else {node.get_name()};
+	let entered = if path.contains(&node_id) {" *"} else {""};
+	let components = get_component_names(comps, node_id);
+	
+	writeln!(out, "{:indent$}{} {}{} [{}]", "", name, node_id, entered, components.join(", "), indent = depth * 2).unwrap();
+	
+	for child in get_children(nodes, node_id) {
+		dump_text(nodes, comps, path, child, depth + 1, out);
+	}
+}
+
+fn dump_json(nodes: &Nodes, comps: &Comps, path: &[NodeId], node_id: NodeId, out: &mut String) {
+	let node = match nodes.get(&node_id) {
+		Some(node) => node,
+		None => {
+			out.push_str("null");
+			return
+		},
+	};
+	
+	write!(out, "{{\"id\":{},\"name\":", node_id.get_inner()).unwrap();
+	write_json_string(node.get_name(), out);
+	write!(out, ",\"entered\":{},\"components\":[", path.contains(&node_id)).unwrap();
+	
+	for (index, name) in get_component_names(comps, node_id).iter().enumerate() {
+		if index > 0 {
+			out.push(',');
+		}
+		write_json_string(name, out);
+	}
+	
+	out.push_str("],\"children\":[");
+	
+	for (index, child) in get_children(nodes, node_id).into_iter().enumerate() {
+		if index > 0 {
+			out.push(',');
+		}
+		dump_json(nodes, comps, path, child, out);
+	}
+	
+	out.push_str("]}");
+}
+
+fn write_json_string(string: &str, out: &mut String) {
+	out.push('"');
+	
+	for c in string.chars() {
+		match c {
+			'"' => out.push_str("\\\""),
+			'\\' => out.push_str("\\\\"),
+			'\n' => out.push_str("\\n"),
+			'\r' => out.push_str("\\r"),
+			'\t' => out.push_str("\\t"),
+			c if c.is_control() => write!(out, "\\u{:04x}", c as u32).unwrap(),
+			c => out.push(c),
+		}
+	}
+	
+	out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::{Backbone, DebugComponent};
+	
+	#[test]
+	fn dump() {
+		let mut backbone = Backbone::new();
+		let root = backbone.root_get_id();
+		let menu = backbone.node_new(root, "menu", None).unwrap();
+		let world = backbone.node_new(root, "wo\"rld", None).unwrap();
+		backbone.node_new(menu, "options", None).unwrap();
+		backbone.node_component_attach(world, DebugComponent);
+		
+		backbone.location_set("/menu").unwrap();
+		backbone.update_until_idle();
+		
+		assert_eq!(backbone.node_get_children(root), vec![menu, world]);
+		assert_eq!(backbone.node_get_by_path("options"), backbone.node_get_by_path("/menu/options"));
+		assert_eq!(backbone.node_component_names(world), vec!["DebugComponent"]);
+		
+		assert_eq!(backbone.tree_dump(root), concat!(
+			"/ #0 * []\n",
+			"  menu #1 * []\n",
+			"    options #3 []\n",
+			"  wo\"rld #2 [DebugComponent]\n",
+		));
+		
+		assert_eq!(
+			backbone.tree_dump_json(world),
+			r#"{"id":2,"name":"wo\"rld","entered":false,"components":["DebugComponent"],"children":[]}"#
+		);
+	}
+}
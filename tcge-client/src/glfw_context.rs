@@ -41,6 +41,7 @@ impl GlfwContext {
 		window.make_current();
 		
 		window.set_key_polling(true);
+		window.set_char_polling(true);
 		window.set_mouse_button_polling(true);
 		window.set_cursor_pos_polling(true);
 		window.set_cursor_mode(glfw::CursorMode::Normal);
@@ -228,6 +229,17 @@ impl backbone::Event for KeyEvent {
 	}
 }
 
+/// A character that was typed, after the keyboard layout was applied.
+pub struct CharEvent {
+	pub character: char,
+}
+
+impl backbone::Event for CharEvent {
+	fn get_type_name(&self) -> &'static str {
+		"CharEvent"
+	}
+}
+
 pub struct MouseEvent {
 	pub button: glfw::MouseButton,
 	pub action: glfw::Action,
@@ -13,11 +13,14 @@ extern crate tcge_blocks as blocks;
 extern crate tcge_opengl as gl;
 
 use common::gameloop;
+use common::Clock;
+use common::commands::{CommandRegistry, Command, ArgKind, Invocation, CommandError, ResponseSink};
+use common::console::{Console, LineKind};
 use backbone::WrapperComponent;
 
 mod glfw_context;
 use glfw_context::GlfwContext;
-use glfw_context::{ResizeEvent, MouseMoveEvent, MouseEvent, KeyEvent};
+use glfw_context::{ResizeEvent, MouseMoveEvent, MouseEvent, KeyEvent, CharEvent};
 use common::resources::ResourceProvider;
 
 mod render;
@@ -25,14 +28,29 @@ mod playground;
 
 const DEFAULT_TICKS_PER_SECOND: i32 = 60;
 
+/// The most ticks a single `step` command may ask for.
+const MAX_STEP_TICKS: i64 = 100_000;
+
+/// The number of lines the in-game console keeps.
+const CONSOLE_SCROLLBACK: usize = 500;
+
+/// The size of the font of the in-game console, in pixels.
+const CONSOLE_FONT_SIZE: f32 = 16.0;
+
 fn main() {
-	init_logger();
+	let remotes = common::commandline::Remotes::new();
+	let scrollback = common::console::Scrollback::new(CONSOLE_SCROLLBACK);
+	init_logger(&remotes, &scrollback);
 	info!("Hello, world!");
 	
 	let mut backbone = backbone::Backbone::new();
 	let root_id = backbone.root_get_id();
 	
-	let root_node_handler = RootNodeHandler {};
+	let root_node_handler = RootNodeHandler {
+		trace_file: None,
+		toggle_char_pending: false,
+		cursor_before_console: None,
+	};
 	let root_node_handler = Box::new(root_node_handler );
 	backbone.set_root_node_handler(root_node_handler);
 	
@@ -43,6 +61,22 @@ fn main() {
 	let resources = WrapperComponent::new("Resources", resources);
 	let resources = backbone.node_component_attach(root_id, resources);
 	
+	let assets = common::assets::Assets::new();
+	let assets = WrapperComponent::new("Assets", assets);
+	let assets = backbone.node_component_attach(root_id, assets);
+	
+	let commands = common::commands::CommandRegistry::new();
+	let commands = WrapperComponent::new("Commands", commands);
+	backbone.node_component_attach(root_id, commands);
+	
+	let console = Console::new(scrollback);
+	let console = WrapperComponent::new("Console", console);
+	backbone.node_component_attach(root_id, console);
+	
+	let loader = common::resources::AsyncLoader::new(2);
+	let loader = WrapperComponent::new("AsyncLoader", loader);
+	let loader = backbone.node_component_attach(root_id, loader);
+	
 	// Register all default (core) resources embedded in the binary
 	let mut includes: common::resources::Includes = vec![];
 	includes.extend(&render::text::TEXT_RENDERER_FILES);
@@ -79,7 +113,9 @@ fn main() {
 	playground::setup(
 		&mut backbone,
 		glfw_context,
-		resources
+		resources,
+		assets,
+		loader
 	);
 	
 	backbone.location_set("/playground").unwrap();
@@ -96,13 +132,13 @@ fn main() {
 	
 	info!("Starting gameloop with {} ticks per second.", gameloop.get_ticks_per_second());
 	
-	main_loop(&mut backbone, glfw_context, gameloop);
+	main_loop(&mut backbone, glfw_context, gameloop, remotes);
 	
 	// The End.
 	info!("Goodbye, world!");
 }
 
-fn init_logger() {
+fn init_logger(remotes: &common::commandline::Remotes, scrollback: &common::console::Scrollback) {
 	use simplelog::*;
 	let current_exe = std::env::current_exe().expect("Failed to get path of the 'client' executable.");
 	let current_dir = current_exe.parent().expect("Failed to get path of the 'client' executables parent directory.");
@@ -117,17 +153,88 @@ fn init_logger() {
 		vec![
 			TermLogger::new(LevelFilter::Trace, log_config, TerminalMode::Mixed).unwrap(),
 			WriteLogger::new(LevelFilter::Info, log_config, log_file),
+			Box::new(SharedLog(common::commandline::RemoteLogger {
+				remotes: remotes.clone(),
+				level: LevelFilter::Info,
+			}, LevelFilter::Info)),
+			Box::new(SharedLog(common::console::ScrollbackLogger {
+				scrollback: scrollback.clone(),
+				level: LevelFilter::Info,
+			}, LevelFilter::Info)),
 		]
 	).expect("Failed to initialize simplelog::CombinedLogger");
 }
 
+/// Lets the `CombinedLogger` stream log output to remote consoles and the in-game console.
+struct SharedLog<L: log::Log>(L, log::LevelFilter);
+
+impl<L: log::Log> log::Log for SharedLog<L> {
+	fn enabled(&self, metadata: &log::Metadata) -> bool {
+		log::Log::enabled(&self.0, metadata)
+	}
+	
+	fn log(&self, record: &log::Record) {
+		log::Log::log(&self.0, record)
+	}
+	
+	fn flush(&self) {}
+}
+
+impl<L: log::Log + 'static> simplelog::SharedLogger for SharedLog<L> {
+	fn level(&self) -> log::LevelFilter {
+		self.1
+	}
+	
+	fn config(&self) -> Option<&simplelog::Config> {
+		None
+	}
+	
+	fn as_log(self: Box<Self>) -> Box<dyn log::Log> {
+		Box::new(*self)
+	}
+}
+
+/// Returns where remote consoles may connect to, as given by `--console <tcp:port|unix:path>`.
+fn console_address() -> Option<common::commandline::RemoteAddr> {
+	let mut args = std::env::args().skip_while(|arg| arg != "--console").skip(1);
+	let address = args.next()?;
+	
+	match address.parse() {
+		Ok(address) => Some(address),
+		Err(e) => {
+			error!("{}", e);
+			None
+		}
+	}
+}
+
 fn main_loop(
 	backbone: &mut backbone::Backbone,
 	glfw_context: &mut GlfwContext,
 	gameloop: &mut gameloop::State,
+	remotes: common::commandline::Remotes,
 ) {
-	let command_line = common::commandline::CommandLine::new();
-	let mut last_escape_press = common::current_time_nanos();
+	let completer = backbone.component_get::<WrapperComponent<CommandRegistry>>()
+		.map(|registry| registry.completer())
+		.unwrap_or_default();
+	let history = common::resources::get_exe_path().ok()
+		.map(|path| path.join("console_history.txt"));
+	let mut command_line = common::commandline::CommandLine::new(completer.clone(), history);
+	
+	// The in-game console enters its commands into the same queue.
+	if let Ok(console) = backbone.component_get_mut::<WrapperComponent<Console>>() {
+		console.connect(command_line.sender());
+		console.set_completer(completer);
+	}
+	
+	if let Some(address) = console_address() {
+		if let Err(e) = command_line.listen(&address, remotes) {
+			error!("Failed to listen for remote consoles on {:?}: {}", address, e);
+		}
+	}
+	let clock = common::clock::RealClock::new();
+	let mut last_escape_press = clock.now_nanos();
+	let mut tree_revision = None;
 	let mut cursor = (0.0, 0.0);
 	
 	use smol::Task;
@@ -160,22 +267,75 @@ fn main_loop(
 				continue
 			}
 			
-			while let Some(command) = command_line.recv() {
-				backbone.fire_event(&mut CommandEvent {
-					command
+			while let Some(input) = command_line.recv() {
+				let invocation = match backbone.component_get::<WrapperComponent<CommandRegistry>>() {
+					Ok(registry) => registry.parse(&input.line),
+					Err(e) => {
+						input.sink.failure(format!("No command registry: {}", e));
+						continue
+					},
+				};
+				
+				let invocation = match invocation {
+					Ok(invocation) => invocation,
+					Err(err) => {
+						input.sink.failure(err.to_string());
+						continue
+					},
+				};
+				
+				// Commands are not passive, so the backbone would drop them while it is busy.
+				if backbone.get_state() != &backbone::State::Idle {
+					input.sink.failure(format!("The backbone is busy ({}), try again.", backbone.get_state()));
+					continue
+				}
+				
+				let mut event = CommandEvent {
+					command: input.line,
+					invocation,
+					sink: input.sink,
+					handled: false,
+				};
+				
+				backbone.fire_event(&mut event);
+				
+				if !event.handled {
+					event.sink.failure(format!("Nothing here handles '{}'.", event.invocation.get_name()));
+				}
+			}
+			
+			// Deliver the resources that finished loading in the background.
+			let completions: Vec<_> = match backbone.component_get_mut::<WrapperComponent<common::resources::AsyncLoader>>() {
+				Ok(loader) => std::iter::from_fn(|| loader.poll()).collect(),
+				Err(_) => vec![],
+			};
+			
+			for completion in completions {
+				backbone.fire_event(&mut ResourceLoadedEvent {
+					completion
 				});
 			}
 			
+			// Nodes come and go, so the paths offered for completion are kept up to date.
+			if tree_revision != Some(backbone.tree_revision()) {
+				tree_revision = Some(backbone.tree_revision());
+				let paths = node_paths(backbone.nodes_get());
+				
+				if let Ok(registry) = backbone.component_get_mut::<WrapperComponent<CommandRegistry>>() {
+					registry.set_vocabulary(ArgKind::Path, paths);
+				}
+			}
+			
 			glfw_context.update();
 			
 			let loop_state = gameloop.update(
-				|| glfw_context.glfw.get_time()
+				&|| glfw_context.glfw.get_time()
 			);
 			
 			for (_time, event) in glfw::flush_messages(&glfw_context.events) {
 				match event {
 					glfw::WindowEvent::Key(glfw::Key::Escape, _, glfw::Action::Press, _) => {
-						let current = common::current_time_nanos();
+						let current = clock.now_nanos();
 						
 						if (current - last_escape_press) < 500_000_000 {
 							info!("User pressed ESC twice, shutting down...");
@@ -194,6 +354,14 @@ fn main_loop(
 						});
 					},
 					
+					glfw::WindowEvent::Char(character) => {
+						common::profiler::scope("char", || {
+							backbone.fire_event(&mut CharEvent {
+								character
+							});
+						});
+					},
+					
 					glfw::WindowEvent::MouseButton(button, action, modifiers) => {
 						common::profiler::scope("mouse", || {
 							backbone.fire_event(&mut MouseEvent {
@@ -280,13 +448,27 @@ fn main_loop(
 				},
 				
 				gameloop::Timer(fps, tps) => {
+					// Check for changed resources once per second.
+					if let Ok(resources) = backbone.component_get_mut::<WrapperComponent<common::Resources>>() {
+						resources.poll_watchers();
+					}
+					
+					// ...and forget the assets nobody holds on to anymore.
+					if let Ok(assets) = backbone.component_get_mut::<WrapperComponent<common::assets::Assets>>() {
+						let forgotten = assets.collect_garbage();
+						if forgotten > 0 {
+							debug!("Forgot {} unused assets.", forgotten);
+						}
+					}
+					
 					use std::fmt::Write;
 					glfw_context.title_dyn.clear();
 					write!(
 						glfw_context.title_dyn,
-						"{} - {:.0} FPS, {:.0} TPS - {}",
+						"{} - {:.0} FPS, {:.0} TPS{} - {}",
 						glfw_context.title_fin,
 						fps, tps,
+						if gameloop.is_paused() {" (paused)"} else {""},
 						backbone.location_get_str()
 					).unwrap();
 					glfw_context.window.set_title(glfw_context.title_dyn.as_str());
@@ -321,6 +503,11 @@ pub struct TickEvent {
 }
 
 impl backbone::Event for TickEvent {
+	// Background nodes need to tick too, wherever the backbone is.
+	fn get_dispatch(&self) -> backbone::Dispatch {
+		backbone::Dispatch::Broadcast
+	}
+	
 	fn get_type_name(&self) -> &'static str {
 		"TickEvent"
 	}
@@ -341,8 +528,31 @@ impl backbone::Event for RenderEvent {
 	}
 }
 
+/// Fired when a resource requested from the `AsyncLoader` component finished loading.
+pub struct ResourceLoadedEvent {
+	pub completion: common::resources::Completion,
+}
+
+impl backbone::Event for ResourceLoadedEvent {
+	fn is_passive(&self) -> bool {
+		true
+	}
+	
+	fn get_type_name(&self) -> &'static str {
+		"ResourceLoadedEvent"
+	}
+}
+
+/// Fired for every command line that was parsed by the `CommandRegistry`.
+///
+/// The handler of the command must answer it trough the `sink`, and mark it as `handled`.
 pub struct CommandEvent {
-	pub command: String
+	pub command: String,
+	pub invocation: common::commands::Invocation,
+	pub sink: ResponseSink,
+	
+	/// Whether a handler took care of answering the command.
+	pub handled: bool,
 }
 
 impl backbone::Event for CommandEvent {
@@ -351,136 +561,468 @@ impl backbone::Event for CommandEvent {
 	}
 }
 
-struct RootNodeHandler {}
+struct RootNodeHandler {
+	/// Where to write the profiler capture to once it is complete, and who to tell about it.
+	trace_file: Option<(String, ResponseSink)>,
+	
+	/// Is the next character typed by the key that toggled the console?
+	toggle_char_pending: bool,
+	
+	/// The cursor mode from before the console was opened, restored when it is closed.
+	cursor_before_console: Option<glfw::CursorMode>,
+}
 
 impl backbone::Handler for RootNodeHandler {
-	fn on_event<'a>(&mut self, event: &mut backbone::Wrapper, context: &mut backbone::Context) {
-		let phase = event.get_phase().clone();
+	fn subscribe(&self, subscriptions: &mut backbone::Subscriptions) {
+		use backbone::Phase::*;
+		subscriptions
+			.listen(&[Propagation], 0, RootNodeHandler::on_command_event)
+			.listen(&[Propagation], 0, RootNodeHandler::on_mouse_move)
+			.listen(&[Propagation], 0, RootNodeHandler::on_mouse)
+			.listen(&[Propagation], 0, RootNodeHandler::on_char)
+			.listen(&[Propagation], 0, RootNodeHandler::on_key)
+			// The frame is cleared before and presented after everyone else has rendered.
+			.listen(&[Propagation], i32::max_value(), RootNodeHandler::on_render_clear)
+			.listen(&[Bubbling], i32::min_value(), RootNodeHandler::on_render_present);
+	}
+	
+	fn on_enter(&mut self, context: &mut backbone::Context) {
+		let registry = match context.component_get_mut::<WrapperComponent<CommandRegistry>>() {
+			Ok(registry) => registry,
+			Err(_) => return,
+		};
 		
-		let glfw_context = context
-			.component_get_mut::<GlfwContext>()
-			.ok().unwrap();
+		registry.register(Command::new("stop", "Stops the application.").alias("exit"));
+		registry.register(Command::new("echo", "Prints the given text.")
+			.arg("text", ArgKind::Text));
+		registry.register(Command::new("loc", "Moves the backbone to the given location, or 'back' and 'forward' in its history.")
+			.arg("path", ArgKind::Path));
+		registry.register(Command::new("tree", "Lists the nodes below the given path, as 'text' or 'json'.")
+			.optional("path", ArgKind::Path)
+			.optional("format", ArgKind::Word));
+		registry.register(Command::new("profiler-capture", "Records the given number of frames into a Chrome trace file.")
+			.arg("frames", ArgKind::Int)
+			.arg("file", ArgKind::Word));
+		registry.register(Command::new("set-tps", "Changes the tick-rate, or resets it to the default.")
+			.optional("rate", ArgKind::Int));
+		registry.register(Command::new("pause", "Pauses the simulation, while rendering continues."));
+		registry.register(Command::new("resume", "Resumes the simulation after a pause."));
+		registry.register(Command::new("step", "Pauses the simulation and runs the given number of ticks.")
+			.optional("ticks", ArgKind::Int));
+		registry.register(Command::new("time-scale", "Runs the simulation slower or faster than real time.")
+			.arg("factor", ArgKind::Float));
+	}
+}
+
+impl RootNodeHandler {
+	fn on_command_event(&mut self, cmd: &mut CommandEvent, flow: &mut backbone::Flow, context: &mut backbone::Context) {
+		let result = self.on_command(&cmd.invocation, &cmd.sink, flow, context);
 		
-		if let Some(cmd) = event.downcast_mut::<CommandEvent>() {
-			// 'steal' the command from the struct to avoid the borrow checker
-			let command = &mut cmd.command;
-			
-			if command.as_str() == "stop" {
-				event.stop();
-				context.component_get_mut::<WrapperComponent<gameloop::State>>()
-					.map(|gameloop| {
-						gameloop.stop();
-					}).ok();
-				event.stop();
-				return;
+		match result {
+			Ok(true) => {
+				cmd.handled = true;
+				flow.stop();
+			},
+			Ok(false) => (),
+			Err(err) => {
+				cmd.sink.failure(err.to_string());
+				cmd.handled = true;
+				flow.stop();
 			}
-			
-			if command.starts_with("echo ") {
-				if let Some(mid) = command.find(' ') {
-					let (_, echo) = command.split_at(mid);
-					let echo = echo.trim().to_string();
-					info!("Echo: {}", echo);
-				}
-				event.stop();
-				return;
+		}
+	}
+	
+	fn on_mouse_move(&mut self, _event: &mut MouseMoveEvent, flow: &mut backbone::Flow, context: &mut backbone::Context) {
+		let glfw_context = match context.component_get::<GlfwContext>() {
+			Ok(glfw_context) => glfw_context,
+			Err(e) => {
+				error!("Failed to handle mouse movement: {}", e);
+				return
 			}
-			
-			if command.starts_with("loc ") {
-				if let Some(mid) = command.find(' ') {
-					let (_, path) = command.split_at(mid);
-					let path = path.trim().to_string();
-					info!("Attempting to move to path: {}", path);
-					event.new_state(backbone::State::Move(path, 0));
-				}
-				event.stop();
-				return;
+		};
+		
+		if glfw_context.window.get_cursor_mode() != glfw::CursorMode::Disabled {
+			flow.stop();
+		}
+	}
+	
+	fn on_mouse(&mut self, _event: &mut MouseEvent, flow: &mut backbone::Flow, context: &mut backbone::Context) {
+		if console_is_open(context) {
+			flow.stop();
+		}
+	}
+	
+	fn on_char(&mut self, char_event: &mut CharEvent, flow: &mut backbone::Flow, context: &mut backbone::Context) {
+		// The key that toggles the console also types a character, whichever it is on this layout.
+		if self.toggle_char_pending {
+			self.toggle_char_pending = false;
+			flow.stop();
+			return
+		}
+		
+		if let Ok(console) = context.component_get_mut::<WrapperComponent<Console>>() {
+			if console.is_open() {
+				console.insert(char_event.character);
+				flow.stop();
 			}
-			
-			if command.starts_with("set-tps ") {
-				let mut tps = DEFAULT_TICKS_PER_SECOND;
-				if let Some(mid) = command.find(' ') {
-					let (_, num) = command.split_at(mid);
-					let num = num.trim();
-					match num.parse::<i32>() {
-						Ok(num) => tps = num,
-						Err(err) => {
-							error!("Could not parse number '{}': {}", num, err);
-							event.stop();
-							return
-						},
+		}
+	}
+	
+	fn on_key(&mut self, key_event: &mut KeyEvent, flow: &mut backbone::Flow, context: &mut backbone::Context) {
+		// A character only follows the key event that typed it; a dead key types nothing.
+		self.toggle_char_pending = false;
+		
+		if let Ok(console) = context.component_get_mut::<WrapperComponent<Console>>() {
+			if key_event.key == glfw::Key::GraveAccent && key_event.action == glfw::Action::Press {
+				let open = console.toggle();
+				self.toggle_char_pending = true;
+				
+				if let Ok(glfw_context) = context.component_get_mut::<GlfwContext>() {
+					if open {
+						self.cursor_before_console = Some(glfw_context.window.get_cursor_mode());
+						glfw_context.window.set_cursor_mode(glfw::CursorMode::Normal);
+					} else if let Some(mode) = self.cursor_before_console.take() {
+						glfw_context.window.set_cursor_mode(mode);
 					}
-				} else {
-					error!("No tick-rate given, using default.");
 				}
 				
-				info!("Changing tick-rate to {} tps.", tps);
-				context.component_get_mut::<WrapperComponent<gameloop::State>>()
-					.map(|gameloop| {
-						gameloop.set_ticks_per_second(tps);
-					}).ok();
-				event.stop();
-				return;
+				flow.stop();
+				return
 			}
 			
-			return
+			// Holding the toggle key types it over and over.
+			if key_event.key == glfw::Key::GraveAccent && key_event.action == glfw::Action::Repeat {
+				self.toggle_char_pending = true;
+				flow.stop();
+				return
+			}
+			
+			// While open, the console captures all keys.
+			if console.is_open() {
+				if key_event.action != glfw::Action::Release {
+					console_key(console, key_event.key);
+				}
+				flow.stop();
+				return
+			}
 		}
 		
-		if let Some(_) = event.downcast::<MouseMoveEvent>() {
-			if glfw_context.window.get_cursor_mode() != glfw::CursorMode::Disabled {
-				event.stop();
+		let glfw_context = match context.component_get_mut::<GlfwContext>() {
+			Ok(glfw_context) => glfw_context,
+			Err(e) => {
+				error!("Failed to handle key: {}", e);
+				return
 			}
-			return
-		}
+		};
 		
-		if let Some(_) = event.downcast::<MouseEvent>() {
-			return
+		match key_event {
+			KeyEvent{key: glfw::Key::M, scancode: _, action: glfw::Action::Press, modifiers: _} => {
+				let new_state = toggle_cursor_mode(
+					&mut glfw_context.window,
+					None // toggle
+				);
+				glfw_context.window.set_cursor_mode(new_state);
+				flow.stop();
+			},
+			_ => (),
+		}
+	}
+	
+	fn on_render_clear(&mut self, render_event: &mut RenderEvent, _flow: &mut backbone::Flow, _context: &mut backbone::Context) {
+		unsafe {
+			render_event.gl.ClearColor(0.0, 0.0, 0.0, 1.0);
+			render_event.gl.Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT | gl::STENCIL_BUFFER_BIT);
 		}
+	}
+	
+	fn on_render_present(&mut self, render_event: &mut RenderEvent, _flow: &mut backbone::Flow, context: &mut backbone::Context) {
+		let console = context.component_get::<WrapperComponent<Console>>();
+		let text = context.component_get_mut::<render::text::TextRendererComp>();
 		
-		if let Some(key_event) = event.downcast::<KeyEvent>() {
-			
-			match key_event {
-				KeyEvent{key: glfw::Key::M, scancode: _, action: glfw::Action::Press, modifiers: _} => {
-					let new_state = toggle_cursor_mode(
-						&mut glfw_context.window,
-						None // toggle
-					);
-					glfw_context.window.set_cursor_mode(new_state);
-					event.stop();
-				},
-				_ => (),
+		if let (Ok(console), Ok(text)) = (console, text) {
+			if console.is_open() {
+				draw_console(render_event, console, text);
 			}
-			
-			return
 		}
 		
-		if let Some(_) = event.downcast::<TickEvent>() {
-			return
-		}
+		let glfw_context = match context.component_get_mut::<GlfwContext>() {
+			Ok(glfw_context) => glfw_context,
+			Err(e) => {
+				error!("Failed to present frame: {}", e);
+				return
+			}
+		};
 		
-		if let Some(render_event) = event.downcast::<RenderEvent>() {
-			match phase {
-				backbone::Phase::Creation => {},
+		use glfw::Context;
+		glfw_context.window.swap_buffers();
+		
+		if let Some(capture) = common::profiler::capture_take() {
+			self.write_trace_file(&capture);
+		}
+	}
+	
+	/// Executes the given command, returning whether it was one of the root commands.
+	fn on_command(&mut self, invocation: &Invocation, sink: &ResponseSink, flow: &mut backbone::Flow, context: &mut backbone::Context) -> Result<bool, CommandError> {
+		match invocation.get_name() {
+			"help" => {
+				let registry = context.component_get::<WrapperComponent<CommandRegistry>>()
+					.map_err(|e| CommandError::Failed(format!("No command registry: {}", e)))?;
 				
-				backbone::Phase::Propagation => {
-					unsafe {
-						render_event.gl.ClearColor(0.0, 0.0, 0.0, 1.0);
-						render_event.gl.Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT | gl::STENCIL_BUFFER_BIT);
-					}
-				},
+				let lines = registry.help(invocation.get_str("command"))?;
+				for line in lines.iter() {
+					sink.info(line.as_str());
+				}
 				
-				backbone::Phase::Action => {},
+				sink.success(format!("{} lines of help", lines.len()));
+			},
+			
+			"stop" => {
+				get_gameloop(context)?.stop();
+				sink.success("Stopping.");
+			},
+			
+			"echo" => {
+				sink.success(invocation.get_str("text").unwrap_or(""));
+			},
+			
+			"loc" => {
+				let path = invocation.get_str("path").unwrap_or("/");
 				
-				backbone::Phase::Bubbling => {
-					use glfw::Context;
-					glfw_context.window.swap_buffers();
-				},
-			}
+				// Nodes named like this can still be reached as './back' and './forward'.
+				match path {
+					"back" => {
+						if !context.history_get().can_go_back() {
+							return Err(CommandError::Failed("There is no location to go back to.".to_string()));
+						}
+						
+						flow.new_state(backbone::State::Back);
+						sink.success("Moving back.");
+						return Ok(true)
+					},
+					
+					"forward" => {
+						if !context.history_get().can_go_forward() {
+							return Err(CommandError::Failed("There is no location to go forward to.".to_string()));
+						}
+						
+						flow.new_state(backbone::State::Forward);
+						sink.success("Moving forward.");
+						return Ok(true)
+					},
+					
+					_ => (),
+				}
+				
+				// An invalid path would stop the backbone halfway.
+				context.resolve(path)
+					.map_err(|e| CommandError::Failed(e.to_string()))?;
+				
+				flow.new_state(backbone::State::Move(path.into()));
+				sink.success(format!("Moving to path: {}", path));
+			},
 			
-			return
+			"tree" => {
+				let path = invocation.get_str("path").unwrap_or("/");
+				let node_id = context.resolve(path)
+					.map_err(|e| CommandError::Failed(e.to_string()))?
+					.pop()
+					.unwrap_or(context.current);
+				
+				match invocation.get_str("format").unwrap_or("text") {
+					"text" => {
+						let dump = context.tree_dump(node_id);
+						for line in dump.lines() {
+							sink.info(line);
+						}
+						
+						sink.success(format!("{} nodes", dump.lines().count()));
+					},
+					
+					"json" => {
+						sink.info(context.tree_dump_json(node_id));
+						sink.success("Dumped the tree as JSON.");
+					},
+					
+					format => return Err(CommandError::Failed(
+						format!("Unknown format '{}', expected 'text' or 'json'.", format)
+					)),
+				}
+			},
+			
+			"profiler-capture" => {
+				let frames = invocation.get_int("frames").unwrap_or(0);
+				let file = invocation.get_str("file").unwrap_or("");
+				
+				if frames <= 0 {
+					return Err(CommandError::Failed("Must capture at least one frame.".to_string()));
+				}
+				
+				// Only one capture can be pending, and its caller is still waiting for an answer.
+				if let Some((pending, _)) = &self.trace_file {
+					return Err(CommandError::Failed(format!("Already capturing into {}.", pending)));
+				}
+				
+				// The command is answered once the capture was written.
+				sink.info(format!("Capturing {} frames into {}...", frames, file));
+				common::profiler::capture_start(frames as usize);
+				self.trace_file = Some((file.to_string(), sink.clone()));
+			},
+			
+			"set-tps" => {
+				let tps = invocation.get_int("rate").unwrap_or(DEFAULT_TICKS_PER_SECOND as i64);
+				
+				if tps <= 0 || tps > i32::max_value() as i64 {
+					return Err(CommandError::Failed(format!("Invalid tick-rate: {}", tps)));
+				}
+				
+				get_gameloop(context)?.set_ticks_per_second(tps as i32);
+				sink.success(format!("Changed tick-rate to {} tps.", tps));
+			},
+			
+			"pause" => {
+				get_gameloop(context)?.pause();
+				sink.success("Paused the simulation.");
+			},
+			
+			"resume" => {
+				get_gameloop(context)?.resume();
+				sink.success("Resumed the simulation.");
+			},
+			
+			"step" => {
+				let steps = invocation.get_int("ticks").unwrap_or(1);
+				
+				if steps < 0 || steps > MAX_STEP_TICKS {
+					return Err(CommandError::Failed(format!("Can only step 0 to {} ticks, not {}.", MAX_STEP_TICKS, steps)));
+				}
+				
+				get_gameloop(context)?.step_n(steps as u32);
+				
+				sink.success(format!("Stepping {} ticks.", steps));
+			},
+			
+			"time-scale" => {
+				let scale = invocation.get_float("factor").unwrap_or(1.0);
+				
+				if !get_gameloop(context)?.set_time_scale(scale) {
+					return Err(CommandError::Failed(format!("Invalid time-scale: {}", scale)));
+				}
+				
+				sink.success(format!("Changed time-scale to {}x.", scale));
+			},
+			
+			_ => return Ok(false)
 		}
 		
-		debug!("RootNodeHandler received {} in {}-phase.", event.event.get_type_name(), event.get_phase());
+		Ok(true)
 	}
+	
+	fn write_trace_file(&mut self, capture: &common::profiler::Capture) {
+		let (file, sink) = match self.trace_file.take() {
+			Some(target) => target,
+			None => return,
+		};
+		
+		let result = std::fs::File::create(&file)
+			.map(std::io::BufWriter::new)
+			.and_then(|mut out| capture.write_chrome_trace(&mut out));
+		
+		match result {
+			Ok(()) => sink.success(format!("Wrote {} frames of profiler events to {}", capture.frames, file)),
+			Err(e) => sink.failure(format!("Failed to write profiler capture to {}: {}", file, e)),
+		}
+	}
+}
+
+fn console_is_open(context: &mut backbone::Context) -> bool {
+	context.component_get::<WrapperComponent<Console>>()
+		.map(|console| console.is_open())
+		.unwrap_or(false)
+}
+
+/// Applies a pressed (or repeated) key to the open console.
+fn console_key(console: &mut Console, key: glfw::Key) {
+	match key {
+		glfw::Key::Enter | glfw::Key::KpEnter => {
+			console.submit();
+		},
+		glfw::Key::Backspace => console.backspace(),
+		glfw::Key::Delete => console.delete(),
+		glfw::Key::Left => console.left(),
+		glfw::Key::Right => console.right(),
+		glfw::Key::Home => console.home(),
+		glfw::Key::End => console.end(),
+		glfw::Key::Up => console.history_up(),
+		glfw::Key::Down => console.history_down(),
+		glfw::Key::Tab => console.complete(),
+		glfw::Key::PageUp => console.scroll_up(8),
+		glfw::Key::PageDown => console.scroll_down(8),
+		_ => (),
+	}
+}
+
+/// Draws the scrollback and input line of the console into the lower half of the window.
+fn draw_console(render_event: &RenderEvent, console: &Console, text: &mut render::text::TextRendererComp) {
+	unsafe {
+		render_event.gl.Disable(gl::DEPTH_TEST);
+		render_event.gl.Enable(gl::BLEND);
+		render_event.gl.BlendFunc(gl::ONE, gl::ONE_MINUS_SRC_ALPHA);
+	}
+	
+	text.transform = nalgebra_glm::ortho(
+		0.0, render_event.width as f32,
+		render_event.height as f32, 0.0,
+		-1.0, 1.0
+	);
+	
+	let rows = ((render_event.height as f32 / 2.0) / CONSOLE_FONT_SIZE) as usize;
+	let rows = rows.max(2) - 1;
+	
+	// The input line is at the bottom, with the newest lines right above it.
+	let lines = console.get_visible_lines(rows);
+	let mut y_offset = render_event.height as f32 - CONSOLE_FONT_SIZE * (lines.len() + 1) as f32 - 2.0;
+	
+	for line in lines {
+		let prefix = match line.kind {
+			LineKind::Input | LineKind::Info => String::new(),
+			LineKind::Success => "OK ".to_string(),
+			LineKind::Failure => "ERR ".to_string(),
+			LineKind::Log(level) => format!("[{}] ", level),
+		};
+		
+		text.draw_text(&format!("{}{}", prefix, line.text), CONSOLE_FONT_SIZE, 1.0, y_offset);
+		y_offset += CONSOLE_FONT_SIZE;
+	}
+	
+	let (before, after) = console.get_input().split_at(console.get_cursor());
+	text.draw_text(&format!("] {}|{}", before, after), CONSOLE_FONT_SIZE, 1.0, y_offset);
+}
+
+/// Returns the absolute paths of all nodes, for completion.
+fn node_paths(nodes: &backbone::Nodes) -> Vec<String> {
+	nodes.values()
+		.map(|node| {
+			let mut names = vec![];
+			let mut node = node;
+			
+			while node.get_parent_id() != node.get_id() {
+				names.push(node.get_name());
+				node = match nodes.get(&node.get_parent_id()) {
+					Some(parent) => parent,
+					None => break,
+				};
+			}
+			
+			names.reverse();
+			format!("/{}", names.join("/"))
+		})
+		.collect()
+}
+
+fn get_gameloop<'a>(context: &mut backbone::Context<'a>) -> Result<&'a mut gameloop::State, CommandError> {
+	context.component_get_mut::<WrapperComponent<gameloop::State>>()
+		.map(|gameloop| &mut **gameloop)
+		.map_err(|e| CommandError::Failed(format!("No gameloop: {}", e)))
 }
 
 pub fn toggle_cursor_mode(window: &mut glfw::Window, state: Option<glfw::CursorMode>) -> glfw::CursorMode {
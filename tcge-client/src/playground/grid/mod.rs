@@ -74,8 +74,8 @@ pub struct GridMaterial {
 impl GridMaterial {
 	pub fn new(gl: &gl::Gl, res: &resources::Resources) -> Result<Self, GridMaterialError> {
 		
-		let shader_vert = ResourceLocation::from("core/shaders/grid.vert");
-		let shader_frag = ResourceLocation::from("core/shaders/grid.frag");
+		let shader_vert = ResourceLocation::from_static("core:shaders/grid.vert");
+		let shader_frag = ResourceLocation::from_static("core:shaders/grid.frag");
 		
 		let shader_vert = res.res_as_cstring(&shader_vert)
 			.map_err(GridMaterialError::Resource)?;
@@ -1,7 +1,8 @@
 //! Module for prototyping things.
 
 use crate::glfw_context::{GlfwContext, GlInfo};
-use crate::{backbone, RenderEvent, TickEvent, KeyEvent, MouseEvent, MouseMoveEvent};
+use crate::{backbone, RenderEvent, TickEvent, KeyEvent, MouseEvent, MouseMoveEvent, CommandEvent};
+use crate::common::commands::{CommandRegistry, Command, ArgKind, Invocation, CommandError};
 use crate::common::resources;
 use crate::blocks;
 use crate::render;
@@ -24,44 +25,28 @@ pub mod crosshair;
 pub mod test_blocks;
 use test_blocks::*;
 use common::resources::{ResourceProvider, ResourceLocation};
+use common::assets::{Assets, AssetLoader, AssetErrorKind, Handle};
 
 pub fn setup(
 	backbone: &mut backbone::Backbone,
 	glfw_context: &mut GlfwContext,
 	res: &mut resources::Resources,
+	assets: &mut Assets,
+	loader: &mut resources::AsyncLoader,
 ) {
-	info!("Attempting to load ./assets/playground.toml ...");
-	let config = match res.res_as_string(&resources::ResourceLocation::from_str("playground.toml")) {
-		Ok(config) => match toml::from_str(&config) {
-			Ok(config) => if let toml::Value::Table(config) = config {
-				info!("Loaded configuration.");
-				config
-			} else {
-				error!("Root is not a table in playground.toml");
-				toml::value::Table::new()
-			}
-			Err(e) => {
-				error!("Failed to parse playground.toml: {}", e.to_string());
-				toml::value::Table::new()
-			}
-		},
-		Err(_e) => {
-			error!("Failed to read playground.toml");
-			toml::value::Table::new()
-		}
-	};
-	
+	assets.register_loader(TomlLoader);
+	assets.register_loader(ImageLoader);
 	
+	let config = load_config(res, assets);
+	let resource_changes = res.subscribe();
 	
 	let entity_universe = Universe::new();
 	let mut entity_world = entity_universe.create_world();
 	
 	let mut freecam = Freecam::new();
 	
-	if let Some(t) = config.get("freecam") {
-		if let toml::Value::Table(t) = t {
-			freecam.config(t);
-		}
+	if let Some(toml::Value::Table(t)) = config.as_ref().and_then(|config| config.get("freecam")) {
+		freecam.config(t);
 	}
 	
 	// Create the player entity
@@ -98,28 +83,16 @@ pub fn setup(
 		block_models.insert(*id, block_model);
 	}
 	
-	let mut block_atlas = render::TextureAtlasBuilder::new(64);
+	let missing_sprite = MISSING_SPRITE.to_string();
+	let block_sprites = load_block_sprites(res, loader, block_models.values()
+		.flat_map(|model| model.textures.iter())
+		.chain(std::iter::once(&missing_sprite))
+	);
 	
-	for (_, model) in &block_models {
-		for texture in &model.textures {
-			if ! block_atlas.contains(&texture) {
-				let path = format!("core/textures/blocks/{}.png", texture).into();
-				let buffer = res.res_as_buffer(&path).expect("Could not load block sprite.");
-				let sprite = image::load_from_memory(&buffer).expect("Could not decode image buffer.");
-				block_atlas.insert(texture, &sprite);
-			}
-		}
-	}
+	let (block_atlas, bakery) = bake_block_atlas(&glfw_context.gl, res, &blocks, &block_models, &block_sprites)
+		.expect("Failed to bake the block atlas.");
 	
-	let block_atlas = block_atlas.finish(&glfw_context.gl).expect("Failed to upload block atlas to GPU.");
-	
-	let bakery = StaticBlockBakery::new(
-		res, &blocks, &block_models,
-		&|name| block_atlas.sprites.get(name).map(|s|BlockUv::from(s))
-	).expect("StaticBlockBakery initialization must not fail");
-	let bakery = Rc::new(bakery);
-	
-	let chunks = ChunkStorage::new(&blocks);
+	let chunks = ChunkStorage::new(&blocks, Box::new(common::clock::RealClock::new()));
 	
 	let chunks_renderer = ChunkRenderManager::new(
 		&glfw_context.gl,
@@ -148,10 +121,13 @@ pub fn setup(
 	let crosshair_3d = crosshair::CrosshairRenderer3D::new(&glfw_context.gl, &solid_color_material);
 	
 	let playground = Playground {
+		resource_changes,
 		entity_universe,
 		entity_world,
 		entity_player,
 		blocks,
+		block_models,
+		block_sprites,
 		block_atlas,
 		chunks,
 		chunks_renderer,
@@ -169,11 +145,179 @@ pub fn setup(
 	).unwrap();
 }
 
+/// Packs the given sprites into an atlas on the GPU, and bakes the block models with it.
+fn bake_block_atlas(
+	gl: &gl::Gl,
+	res: &resources::Resources,
+	blocks: &blocks::BlocksRef,
+	block_models: &rustc_hash::FxHashMap<blocks::BlockId, BlockModel>,
+	block_sprites: &rustc_hash::FxHashMap<String, image::DynamicImage>,
+) -> Result<(render::TextureAtlas, StaticBlockBakeryRef), String> {
+	let mut block_atlas = render::TextureAtlasBuilder::new(64);
+	
+	for (texture, sprite) in block_sprites {
+		block_atlas.insert(texture.as_str(), sprite);
+	}
+	
+	let block_atlas = block_atlas.finish(gl)
+		.map_err(|e| format!("Failed to upload block atlas to GPU: {:?}", e))?;
+	
+	let bakery = StaticBlockBakery::new(
+		res, blocks, block_models,
+		&|name| block_atlas.sprites.get(name)
+			.or_else(|| block_atlas.sprites.get(MISSING_SPRITE))
+			.map(|s|BlockUv::from(s))
+	).map_err(|_| "Failed to bake the block models.".to_string())?;
+	
+	Ok((block_atlas, Rc::new(bakery)))
+}
+
+/// Decodes the sprite read by the given completion.
+fn decode_sprite(completion: &resources::Completion) -> Result<image::DynamicImage, String> {
+	match &completion.result {
+		Ok(buffer) => image::load_from_memory(buffer).map_err(|e| e.to_string()),
+		Err(e) => Err(format!("{:?}", e)),
+	}
+}
+
+/// Reads and decodes the sprite at the given location.
+fn read_sprite(res: &resources::Resources, location: &ResourceLocation) -> Result<image::DynamicImage, String> {
+	let buffer = res.res_as_buffer(location)
+		.map_err(|e| format!("{:?}", e))?;
+	
+	image::load_from_memory(&buffer).map_err(|e| e.to_string())
+}
+
+/// Returns the name of the block sprite at the given location, if it is one.
+fn sprite_name(location: &ResourceLocation) -> Option<&str> {
+	if location.domain() != "core" {
+		return None;
+	}
+	
+	location.path()
+		.strip_prefix("textures/blocks/")
+		.and_then(|name| name.strip_suffix(".png"))
+}
+
+/// The block texture used in place of those that fail to load.
+const MISSING_SPRITE: &str = "missingno";
+
+/// Textures that fail to load are logged and left out.
+/// Must be called before anything else uses the loader, as other completions are dropped.
+fn load_block_sprites<'t>(
+	res: &resources::Resources,
+	loader: &mut resources::AsyncLoader,
+	textures: impl Iterator<Item = &'t String>
+) -> rustc_hash::FxHashMap<String, image::DynamicImage> {
+	let mut requested = rustc_hash::FxHashSet::default();
+	let mut pending = rustc_hash::FxHashMap::default();
+	
+	for texture in textures {
+		if !requested.insert(texture) {
+			continue;
+		}
+		
+		let location = match ResourceLocation::new("core", &format!("textures/blocks/{}.png", texture)) {
+			Ok(location) => location,
+			Err(e) => {
+				error!("Invalid block texture '{}': {}", texture, e);
+				continue
+			}
+		};
+		
+		pending.insert(loader.res_as_buffer_async(res, location), texture.clone());
+	}
+	
+	let mut sprites = rustc_hash::FxHashMap::default();
+	
+	while !pending.is_empty() {
+		let completion = match loader.wait() {
+			Some(completion) => completion,
+			None => break,
+		};
+		
+		let texture = match pending.remove(&completion.id) {
+			Some(texture) => texture,
+			None => {
+				warn!("Dropped unexpected completion for {}", completion.location);
+				continue
+			}
+		};
+		
+		match decode_sprite(&completion) {
+			Ok(sprite) => {
+				sprites.insert(texture, sprite);
+			},
+			Err(e) => error!("Could not load block sprite {}: {}", completion.location, e),
+		}
+	}
+	
+	sprites
+}
+
+const CONFIG_LOCATION: &str = "core:playground.toml";
+
+/// Where the configuration was read from before resources were namespaced, relative to the executable.
+const LEGACY_CONFIG_PATH: &str = "assets/playground.toml";
+
+/// Loads the configuration, returning a handle to it that is shared with the asset cache.
+fn load_config(res: &resources::Resources, assets: &mut Assets) -> Option<Handle<toml::value::Table>> {
+	info!("Attempting to load ./assets/core/playground.toml ...");
+	match assets.load::<toml::value::Table>(res, &ResourceLocation::from_static(CONFIG_LOCATION)) {
+		Ok(config) => {
+			info!("Loaded configuration.");
+			Some(config)
+		},
+		Err(e) => {
+			error!("{}", e);
+			
+			let legacy = resources::get_exe_path().map(|path| path.join(LEGACY_CONFIG_PATH));
+			if let Ok(legacy) = legacy {
+				if legacy.is_file() {
+					warn!("The configuration moved from ./{} to ./assets/core/playground.toml, please move {}.", LEGACY_CONFIG_PATH, legacy.display());
+				}
+			}
+			
+			None
+		}
+	}
+}
+
+/// Loads a resource as TOML document, whose root must be a table.
+pub struct TomlLoader;
+impl AssetLoader for TomlLoader {
+	type Asset = toml::value::Table;
+	
+	fn load(&self, res: &resources::Resources, location: &ResourceLocation) -> Result<Self::Asset, AssetErrorKind> {
+		let source = res.res_as_string(location).map_err(AssetErrorKind::Resource)?;
+		
+		match toml::from_str(&source) {
+			Ok(toml::Value::Table(table)) => Ok(table),
+			Ok(_) => Err(AssetErrorKind::Parse("Root is not a table".to_string())),
+			Err(e) => Err(AssetErrorKind::Parse(e.to_string())),
+		}
+	}
+}
+
+/// Loads and decodes a resource as image.
+pub struct ImageLoader;
+impl AssetLoader for ImageLoader {
+	type Asset = image::DynamicImage;
+	
+	fn load(&self, res: &resources::Resources, location: &ResourceLocation) -> Result<Self::Asset, AssetErrorKind> {
+		let buffer = res.res_as_buffer(location).map_err(AssetErrorKind::Resource)?;
+		image::load_from_memory(&buffer).map_err(|e| AssetErrorKind::Parse(e.to_string()))
+	}
+}
+
 pub struct Playground {
+	resource_changes: std::sync::mpsc::Receiver<ResourceLocation>,
 	entity_universe: legion::world::Universe,
 	entity_world: legion::world::World,
 	entity_player: legion::entity::Entity,
 	blocks: blocks::BlocksRef,
+	block_models: rustc_hash::FxHashMap<blocks::BlockId, BlockModel>,
+	block_sprites: rustc_hash::FxHashMap<String, image::DynamicImage>,
 	block_atlas: render::TextureAtlas,
 	chunks: ChunkStorage,
 	chunks_renderer: ChunkRenderManager,
@@ -184,162 +328,346 @@ pub struct Playground {
 }
 
 impl backbone::Handler for Playground {
-	fn on_event<'a>(&mut self, event: &mut backbone::Wrapper, context: &mut backbone::Context) {
-		let phase = event.get_phase().clone();
+	fn subscribe(&self, subscriptions: &mut backbone::Subscriptions) {
+		use backbone::Phase::*;
+		subscriptions
+			.listen(&[Propagation], 0, Playground::on_command_event)
+			.listen(&[Action], 0, Playground::on_mouse_move)
+			.listen(&[Propagation], 0, Playground::on_mouse)
+			.listen(&[Action], 0, Playground::on_key)
+			.listen(&[Action], 0, Playground::on_tick)
+			.listen(&[Action], 0, Playground::on_render_scene)
+			.listen(&[Bubbling], 0, Playground::on_render_hud);
+	}
+	
+	fn on_enter(&mut self, context: &mut backbone::Context) {
+		let scope = context.path_str;
+		let registry = match context.component_get_mut::<backbone::WrapperComponent<CommandRegistry>>() {
+			Ok(registry) => registry,
+			Err(_) => return,
+		};
+		
+		registry.set_vocabulary(ArgKind::Block, self.blocks.get_blocks().values()
+			.map(|block| block.get_name().to_string())
+			.collect()
+		);
 		
-		if let Some(mouse_move_event) = event.downcast::<MouseMoveEvent>() {
-			
-			let mut camera  = self.entity_world.get_component_mut::<Freecam>(self.entity_player).expect("player entity freecam component");
-			
-			camera.update_rotation(
-				mouse_move_event.dx as f32,
-				mouse_move_event.dy as f32
-			);
-			
-			let mut rc = camera.get_block_raytrace(16.0, 1.0);
-			if let Some((_, curr, _)) = self.chunks.raycast(&mut rc) {
-				camera.target = Some(curr);
-			} else {
-				camera.target = None;
-			}
-			
-			return
+		registry.register_scoped(scope, Command::new("set-block", "Places a block at the given position.")
+			.alias("setblock")
+			.arg("position", ArgKind::Coord)
+			.arg("block", ArgKind::Block));
+	}
+	
+	fn on_leave(&mut self, context: &mut backbone::Context) {
+		let scope = context.path_str;
+		if let Ok(registry) = context.component_get_mut::<backbone::WrapperComponent<CommandRegistry>>() {
+			registry.unregister_scope(scope);
+			registry.set_vocabulary(ArgKind::Block, vec![]);
+		}
+	}
+}
+
+impl Playground {
+	
+	fn on_command_event(&mut self, cmd: &mut CommandEvent, flow: &mut backbone::Flow, _context: &mut backbone::Context) {
+		let result = match cmd.invocation.get_name() {
+			"set-block" => self.command_set_block(&cmd.invocation),
+			_ => return
+		};
+		
+		match result {
+			Ok(message) => cmd.sink.success(message),
+			Err(err) => cmd.sink.failure(err.to_string()),
 		}
 		
-		if let Some(mouse_event) = event.downcast::<MouseEvent>() {
-			match mouse_event {
-				MouseEvent{button, action: glfw::Action::Press, modifiers: _} => {
-					let used_block  = self.entity_world.get_component::<Inventory>(self.entity_player).expect("player entity freecam component").block;
-					
-					let air = self.blocks
-						.get_block_by_name_unchecked("air")
-						.get_default_state();
-					
-					let bedrock = self.blocks
-						.get_block_by_name_unchecked("color_white")
-						.get_default_state();
-					
-					let camera  = self.entity_world.get_component_mut::<Freecam>(self.entity_player).expect("player entity freecam component");
-					
-					if ! camera.active {
-						return;
-					}
-					
-					let used_block = used_block.unwrap_or(bedrock);
-					
-					let mut rc = camera.get_block_raytrace(16.0, 1.0);
-					
-					if let Some((last, curr, _)) = self.chunks.raycast(&mut rc) {
-						let t = match button {
-							glfw::MouseButtonLeft => {
-								Some((&curr, air))
-							},
-							glfw::MouseButtonRight => {
-								Some((&last, used_block))
-							},
-							_ => None
-						};
-						
-						if let Some((pos, block)) = t {
-							self.chunks.set_block(&pos, block);
-						}
-					}
-					
-				},
-				_ => (),
-			};
-			
-			event.stop();
-			return
+		cmd.handled = true;
+		flow.stop();
+	}
+	
+	fn on_mouse_move(&mut self, mouse_move_event: &mut MouseMoveEvent, _flow: &mut backbone::Flow, _context: &mut backbone::Context) {
+		let mut camera  = self.entity_world.get_component_mut::<Freecam>(self.entity_player).expect("player entity freecam component");
+		
+		camera.update_rotation(
+			mouse_move_event.dx as f32,
+			mouse_move_event.dy as f32
+		);
+		
+		let mut rc = camera.get_block_raytrace(16.0, 1.0);
+		if let Some((_, curr, _)) = self.chunks.raycast(&mut rc) {
+			camera.target = Some(curr);
+		} else {
+			camera.target = None;
 		}
+	}
+	
+	fn on_mouse(&mut self, mouse_event: &mut MouseEvent, flow: &mut backbone::Flow, _context: &mut backbone::Context) {
+		flow.stop();
 		
-		if let Some(key_event) = event.downcast::<KeyEvent>() {
-			match key_event {
-				KeyEvent{key: glfw::Key::C, scancode: _, action: glfw::Action::Press, modifiers: _} => {
-					let mut camera  = self.entity_world.get_component_mut::<Freecam>(self.entity_player).expect("player entity freecam component");
-					camera.crane = !camera.crane;
-				},
+		match mouse_event {
+			MouseEvent{button, action: glfw::Action::Press, modifiers: _} => {
+				let used_block  = self.entity_world.get_component::<Inventory>(self.entity_player).expect("player entity freecam component").block;
 				
-				KeyEvent{key: glfw::Key::G, scancode: _, action: glfw::Action::Press, modifiers: _} => {
-					let mut camera  = self.entity_world.get_component_mut::<Freecam>(self.entity_player).expect("player entity freecam component");
-					camera.gravity = !camera.gravity;
-				},
+				let air = self.blocks
+					.get_block_by_name_unchecked("air")
+					.get_default_state();
 				
-				KeyEvent{key: glfw::Key::Num1, scancode: _, action: glfw::Action::Press, modifiers: _} => {
-					let mut inventory  = self.entity_world.get_component_mut::<Inventory>(self.entity_player).expect("player entity inventory component");
-					inventory.block = Some(self.blocks.get_block_by_name_unchecked("missingno").get_default_state());
-				},
+				let bedrock = self.blocks
+					.get_block_by_name_unchecked("color_white")
+					.get_default_state();
 				
-				KeyEvent{key: glfw::Key::Num2, scancode: _, action: glfw::Action::Press, modifiers: _} => {
-					let mut inventory  = self.entity_world.get_component_mut::<Inventory>(self.entity_player).expect("player entity inventory component");
-					inventory.block = Some(self.blocks.get_block_by_name_unchecked("color_red").get_default_state());
-				},
+				let camera  = self.entity_world.get_component_mut::<Freecam>(self.entity_player).expect("player entity freecam component");
 				
-				KeyEvent{key: glfw::Key::Num3, scancode: _, action: glfw::Action::Press, modifiers: _} => {
-					let mut inventory  = self.entity_world.get_component_mut::<Inventory>(self.entity_player).expect("player entity inventory component");
-					inventory.block = Some(self.blocks.get_block_by_name_unchecked("color_green").get_default_state());
-				},
+				if ! camera.active {
+					return;
+				}
 				
-				KeyEvent{key: glfw::Key::Num4, scancode: _, action: glfw::Action::Press, modifiers: _} => {
-					let mut inventory  = self.entity_world.get_component_mut::<Inventory>(self.entity_player).expect("player entity inventory component");
-					inventory.block = Some(self.blocks.get_block_by_name_unchecked("color_blue").get_default_state());
-				},
+				let used_block = used_block.unwrap_or(bedrock);
 				
-				KeyEvent{key: glfw::Key::Num5, scancode: _, action: glfw::Action::Press, modifiers: _} => {
-					let mut inventory  = self.entity_world.get_component_mut::<Inventory>(self.entity_player).expect("player entity inventory component");
-					inventory.block = Some(self.blocks.get_block_by_name_unchecked("color_white").get_default_state());
-				},
+				let mut rc = camera.get_block_raytrace(16.0, 1.0);
 				
-				_ => (),
-			}
+				if let Some((last, curr, _)) = self.chunks.raycast(&mut rc) {
+					let t = match *button {
+						glfw::MouseButtonLeft => {
+							Some((&curr, air))
+						},
+						glfw::MouseButtonRight => {
+							Some((&last, used_block))
+						},
+						_ => None
+					};
+					
+					if let Some((pos, block)) = t {
+						self.chunks.set_block(&pos, block);
+					}
+				}
+				
+			},
+			_ => (),
+		};
+	}
+	
+	fn on_key(&mut self, key_event: &mut KeyEvent, _flow: &mut backbone::Flow, _context: &mut backbone::Context) {
+		let block = match key_event {
+			KeyEvent{key: glfw::Key::C, scancode: _, action: glfw::Action::Press, modifiers: _} => {
+				let mut camera  = self.entity_world.get_component_mut::<Freecam>(self.entity_player).expect("player entity freecam component");
+				camera.crane = !camera.crane;
+				return
+			},
 			
-			return
+			KeyEvent{key: glfw::Key::G, scancode: _, action: glfw::Action::Press, modifiers: _} => {
+				let mut camera  = self.entity_world.get_component_mut::<Freecam>(self.entity_player).expect("player entity freecam component");
+				camera.gravity = !camera.gravity;
+				return
+			},
+			
+			KeyEvent{key: glfw::Key::Num1, scancode: _, action: glfw::Action::Press, modifiers: _} => "missingno",
+			KeyEvent{key: glfw::Key::Num2, scancode: _, action: glfw::Action::Press, modifiers: _} => "color_red",
+			KeyEvent{key: glfw::Key::Num3, scancode: _, action: glfw::Action::Press, modifiers: _} => "color_green",
+			KeyEvent{key: glfw::Key::Num4, scancode: _, action: glfw::Action::Press, modifiers: _} => "color_blue",
+			KeyEvent{key: glfw::Key::Num5, scancode: _, action: glfw::Action::Press, modifiers: _} => "color_white",
+			_ => return,
+		};
+		
+		let mut inventory  = self.entity_world.get_component_mut::<Inventory>(self.entity_player).expect("player entity inventory component");
+		inventory.block = Some(self.blocks.get_block_by_name_unchecked(block).get_default_state());
+	}
+	
+	fn on_tick(&mut self, tick: &mut TickEvent, _flow: &mut backbone::Flow, context: &mut backbone::Context) {
+		self.reload_changed_resources(context);
+		
+		let glfw_context = match context.component_get_mut::<GlfwContext>() {
+			Ok(glfw_context) => glfw_context,
+			Err(e) => {
+				error!("Failed to tick playground: {}", e);
+				return
+			}
+		};
+		
+		let mut camera  = self.entity_world.get_component_mut::<Freecam>(self.entity_player).expect("player entity freecam component");
+		
+		camera.active = glfw_context.window.get_cursor_mode() == glfw::CursorMode::Disabled;
+		camera.update_movement(&glfw_context.window, 1.0 / tick.tps as f32, &self.chunks);
+		
+		let mut rc = camera.get_block_raytrace(16.0, 1.0);
+		if let Some((_, curr, _)) = self.chunks.raycast(&mut rc) {
+			camera.target = Some(curr);
+		} else {
+			camera.target = None;
 		}
+	}
+	
+	fn on_render_scene(&mut self, render_event: &mut RenderEvent, _flow: &mut backbone::Flow, context: &mut backbone::Context) {
+		let wire_painter = match context.component_get_mut::<render::wireframe::WireframePainterComp>() {
+			Ok(wire_painter) => wire_painter,
+			Err(e) => {
+				error!("Failed to render playground: {}", e);
+				return
+			}
+		};
 		
-		if let Some(tick) = event.downcast::<TickEvent>() {
-			let glfw_context = context
-				.component_get_mut::<GlfwContext>().ok().unwrap();
-			
-			let mut camera  = self.entity_world.get_component_mut::<Freecam>(self.entity_player).expect("player entity freecam component");
-			
-			camera.active = glfw_context.window.get_cursor_mode() == glfw::CursorMode::Disabled;
-			camera.update_movement(&glfw_context.window, 1.0 / tick.tps as f32, &self.chunks);
-			
-			let mut rc = camera.get_block_raytrace(16.0, 1.0);
-			if let Some((_, curr, _)) = self.chunks.raycast(&mut rc) {
-				camera.target = Some(curr);
-			} else {
-				camera.target = None;
+		self.render_scene(render_event, wire_painter);
+	}
+	
+	fn on_render_hud(&mut self, render_event: &mut RenderEvent, _flow: &mut backbone::Flow, context: &mut backbone::Context) {
+		let (glfw_context, text_renderer) = match context.component_get_pair_mut::<GlfwContext, render::text::TextRendererComp>() {
+			Ok(pair) => pair,
+			Err(e) => {
+				error!("Failed to render playground HUD: {}", e);
+				return
 			}
+		};
+		
+		self.render_hud(render_event, text_renderer, &glfw_context.gl_info);
+	}
+	
+	fn command_set_block(&mut self, invocation: &Invocation) -> Result<String, CommandError> {
+		let (x, y, z) = invocation.get_coord("position").unwrap_or((0, 0, 0));
+		let name = invocation.get_str("block").unwrap_or("air");
+		
+		let state = self.blocks.get_block_by_name(name)
+			.ok_or_else(|| CommandError::UnknownBlock(name.to_string()))?
+			.get_default_state();
+		
+		let pos = blocks::BlockCoord::new(x as blocks::BlockDim, y as blocks::BlockDim, z as blocks::BlockDim);
+		
+		if self.chunks.set_block(&pos, state) {
+			Ok(format!("Placed {} at {}, {}, {}.", name, x, y, z))
+		} else {
+			Err(CommandError::Failed(format!("Could not place {} at {}, {}, {}.", name, x, y, z)))
+		}
+	}
+	
+	
+	/// Re-applies every resource that changed on disk since the last tick.
+	///
+	/// The configuration and shaders are reloaded one by one,
+	/// while the block atlas is rebuilt once for all sprites that changed.
+	pub fn reload_changed_resources(&mut self, context: &mut backbone::Context) {
+		let changes: Vec<ResourceLocation> = self.resource_changes.try_iter().collect();
+		
+		if changes.is_empty() {
 			return
 		}
 		
-		if let Some(render_event) = event.downcast::<RenderEvent>() {
-			let glfw_context = context
-				.component_get::<GlfwContext>().ok().unwrap();
-			
-			if let backbone::Phase::Action = phase {
-				let wire_painter = context
-					.component_get_mut::<render::wireframe::WireframePainterComp>().ok().unwrap();
-				
-				self.render_scene(render_event, wire_painter);
+		let res = match context.component_get::<backbone::WrapperComponent<resources::Resources>>() {
+			Ok(res) => res,
+			Err(e) => {
+				error!("Failed to reload resources: Resources {}", e);
+				return
 			}
-			
-			if let backbone::Phase::Bubbling = phase {
-				let text_renderer = context
-					.component_get_mut::<render::text::TextRendererComp>().ok().unwrap();
+		};
+		
+		let config_location = ResourceLocation::from_static(CONFIG_LOCATION);
+		let mut sprites_changed = false;
+		
+		for location in changes {
+			if location == config_location {
+				self.reload_config(context, res, &location);
+			} else if location.domain() == "core" && location.path().starts_with("shaders/") {
+				self.reload_shader(context, res, &location);
+			} else if let Some(texture) = sprite_name(&location) {
+				let used = texture == MISSING_SPRITE || self.block_models.values()
+					.any(|model| model.textures.iter().any(|t| t == texture));
+				
+				if !used {
+					continue;
+				}
 				
-				self.render_hud(render_event, text_renderer, &glfw_context.gl_info);
+				match read_sprite(res, &location) {
+					Ok(sprite) => {
+						self.block_sprites.insert(texture.to_string(), sprite);
+						sprites_changed = true;
+					},
+					Err(e) => error!("Could not reload block sprite {}: {}", location, e),
+				}
 			}
-			
-			return
 		}
 		
-		debug!("Playground received {} in {}-phase.", event.event.get_type_name(), event.get_phase());
+		if sprites_changed {
+			self.rebuild_block_atlas(context, res);
+		}
+	}
+	
+	fn reload_config(&mut self, context: &mut backbone::Context, res: &resources::Resources, location: &ResourceLocation) {
+		let assets = match context.component_get_mut::<backbone::WrapperComponent<Assets>>() {
+			Ok(assets) => assets,
+			Err(e) => {
+				error!("Failed to reload playground.toml: Assets {}", e);
+				return
+			}
+		};
+		
+		assets.invalidate(location);
+		let config = load_config(res, assets);
+		
+		if let Some(toml::Value::Table(t)) = config.as_ref().and_then(|config| config.get("freecam")) {
+			let mut camera = self.entity_world.get_component_mut::<Freecam>(self.entity_player).expect("player entity freecam component");
+			camera.config(t);
+		}
+	}
+	
+	/// Rebuilds whatever uses the shader at the given location, keeping the old one if that fails.
+	fn reload_shader(&mut self, context: &mut backbone::Context, res: &resources::Resources, location: &ResourceLocation) {
+		let gl = match context.component_get::<GlfwContext>() {
+			Ok(glfw_context) => &glfw_context.gl,
+			Err(e) => {
+				error!("Failed to reload shader {}: GlfwContext {}", location, e);
+				return
+			}
+		};
+		
+		let name = location.file_name();
+		let name = name.rsplit_once('.').map(|(stem, _)| stem).unwrap_or(name);
+		
+		let reloaded = match name {
+			"sky" => sky::SkyRenderer::new(gl, res)
+				.map(|sky| self.sky = sky)
+				.is_ok(),
+			
+			"grid" => grid::GridRenderer::new(gl, res)
+				.map(|grid| self.grid = grid)
+				.is_ok(),
+			
+			"blocks" => self.chunks_renderer.reload_material(res)
+				.is_ok(),
+			
+			"solid-color" => render::materials::SolidColorMaterial::new(gl, res)
+				.map(|material| {
+					let material = Rc::new(material);
+					self.crosshair_2d = crosshair::CrosshairRenderer2D::new(gl, &material);
+					self.crosshair_3d = crosshair::CrosshairRenderer3D::new(gl, &material);
+				})
+				.is_ok(),
+			
+			_ => return,
+		};
+		
+		if reloaded {
+			info!("Reloaded the '{}' shader.", name);
+		} else {
+			error!("Failed to reload the '{}' shader, keeping the old one.", name);
+		}
+	}
+	
+	/// Puts the current block sprites into a new atlas, keeping the old one if that fails.
+	fn rebuild_block_atlas(&mut self, context: &mut backbone::Context, res: &resources::Resources) {
+		let gl = match context.component_get::<GlfwContext>() {
+			Ok(glfw_context) => &glfw_context.gl,
+			Err(e) => {
+				error!("Failed to rebuild the block atlas: GlfwContext {}", e);
+				return
+			}
+		};
+		
+		match bake_block_atlas(gl, res, &self.blocks, &self.block_models, &self.block_sprites) {
+			Ok((block_atlas, bakery)) => {
+				self.chunks_renderer.set_atlas(block_atlas.texture.clone(), bakery);
+				self.block_atlas = block_atlas;
+				info!("Rebuilt the block atlas.");
+			},
+			Err(e) => error!("{}", e),
+		}
 	}
-}
-
-impl Playground {
 	
 	pub fn render_scene(&mut self, render_event: &RenderEvent, wirepainter: &mut render::wireframe::WireframePainter) {
 		use crate::render::*;
@@ -433,10 +761,10 @@ impl Playground {
 		}
 		
 		y_offset += 8.0;
-		let profiler = common::profiler::profiler();
-		let proftree = profiler.get_passive();
 		let mut f_buffer = String::with_capacity(250);
-		Self::draw_profiler_node_text(text, proftree, 0, 0, &mut f_buffer, &mut y_offset);
+		common::profiler::with_last_frame(|proftree| {
+			Self::draw_profiler_node_text(text, proftree, 0, 0, &mut f_buffer, &mut y_offset);
+		});
 		//y_offset += 2.0;
 	}
 	
@@ -455,7 +783,8 @@ impl Playground {
 		
 		f_buffer.clear();
 		use std::fmt::Write;
-		write!(f_buffer, "{}: {}", node.name, node.get_time_as_nanosec())
+		let (min, avg, max) = node.get_stats_as_nanosec();
+		write!(f_buffer, "{}: {} ({} / {} / {})", node.name, node.get_time_as_nanosec(), min, avg, max)
 			.ok().expect("Failed to print profiler node.");
 		
 		text.draw_text(f_buffer, 16.0, 1.0 + (depth as f32 * 24.0), *y_offset);
@@ -56,8 +56,8 @@ pub struct SkyMaterial {
 impl SkyMaterial {
 	pub fn new(gl: &gl::Gl, res: &Resources) -> Result<Self, SkyMaterialError> {
 		
-		let shader_vert = ResourceLocation::from("core/shaders/sky.vert");
-		let shader_frag = ResourceLocation::from("core/shaders/sky.frag");
+		let shader_vert = ResourceLocation::from_static("core:shaders/sky.vert");
+		let shader_frag = ResourceLocation::from_static("core:shaders/sky.frag");
 		
 		let shader_vert = res.res_as_cstring(&shader_vert)
 			.map_err(SkyMaterialError::Resource)?;
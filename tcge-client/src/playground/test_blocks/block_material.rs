@@ -30,8 +30,8 @@ impl BlocksMaterial {
 	pub fn new(gl: &gl::Gl, res: &Resources, atlas: Rc<TextureObject>) -> Result<Self, BlocksMaterialError> {
 		debug!("Loading blocks shader...");
 		
-		let shader_vert = ResourceLocation::from("core/shaders/blocks.vert");
-		let shader_frag = ResourceLocation::from("core/shaders/blocks.frag");
+		let shader_vert = ResourceLocation::from_static("core:shaders/blocks.vert");
+		let shader_frag = ResourceLocation::from_static("core:shaders/blocks.frag");
 		
 		let shader_vert = res.res_as_cstring(&shader_vert)
 			.map_err(BlocksMaterialError::Resource)?;
@@ -6,7 +6,7 @@ pub struct Chunk {
 	pub pos: ChunkCoord,
 	pub blocks: BlocksRef,
 	pub data: Box<[BlockState; CHUNK_VOLUME]>,
-	pub last_update: u128
+	pub last_update: u64
 }
 
 impl Chunk {
@@ -62,6 +62,9 @@ impl Chunk {
 		*self.data.get_unchecked(Self::coord_to_index(x, y, z))
 	}
 	
+	/// Changes a block, returning `None` if it is out of bounds or already the given state.
+	///
+	/// This does *not* touch `last_update`; the `ChunkStorage` stamps it with its clock.
 	pub fn set_block(&mut self, x: BlockDim, y: BlockDim, z: BlockDim, state: BlockState) -> Option<()> {
 		let x = Chunk::clamp_chunk_coord(x)?;
 		let y = Chunk::clamp_chunk_coord(y)?;
@@ -74,7 +77,6 @@ impl Chunk {
 		}
 		
 		self.data[index as usize] = state;
-		self.last_update = current_time_nanos();
 		Some(())
 	}
 }
@@ -12,7 +12,8 @@ pub struct ChunkRenderManager {
 	quad_index: BufferObjectRef,
 	
 	// Dynamic
-	chunks: FxHashMap<ChunkCoord, (u128, ChunkMeshState)>,
+	/// The meshes of the chunks, and when they were built; `None` if they must be built again.
+	chunks: FxHashMap<ChunkCoord, (Option<u64>, ChunkMeshState)>,
 	mesher: MesherThreadState,
 	
 	// Per Frame
@@ -49,6 +50,24 @@ impl ChunkRenderManager {
 		})
 	}
 	
+	/// Recompiles the material, keeping the current block atlas.
+	pub fn reload_material(&mut self, res: &resources::Resources) -> Result<(), BlocksMaterialError> {
+		self.material = BlocksMaterial::new(&self.gl, res, self.material.atlas.clone())?;
+		Ok(())
+	}
+	
+	/// Replaces the block atlas and the bakery that refers into it.
+	///
+	/// All chunks are meshed again, showing their old mesh until then.
+	pub fn set_atlas(&mut self, block_atlas: Rc<TextureObject>, bakery: StaticBlockBakeryRef) {
+		self.material.atlas = block_atlas;
+		self.bakery = bakery;
+		
+		for (time, _) in self.chunks.values_mut() {
+			*time = None;
+		}
+	}
+	
 	pub fn generate_quad_indices(gl: &gl::Gl, max: usize) -> BufferObject {
 		let mut indices: Vec<u16> = vec![];
 		for i in 0..max {
@@ -89,16 +108,14 @@ impl ChunkRenderManager {
 			if self.chunks.contains_key(chunk_pos) {
 				let (time, mesh) = self.chunks.get_mut(chunk_pos).unwrap();
 				
-				if chunk.last_update > *time && max_uploads_per_frame > 0 {
+				if is_stale(*time, chunk.last_update) && max_uploads_per_frame > 0 {
 					max_uploads_per_frame -= 1;
 					
 					let block_data = chunks.get_chunk_with_edges(chunk_pos).unwrap();
 					
-					*time = chunk.last_update;
+					*time = Some(chunk.last_update);
 					
-					let profiler_tree = common::profiler::profiler().get_current();
-					
-					profiler_tree.enter_noguard("mesh-chunk");
+					let profiler_guard = common::profiler::enter("mesh-chunk");
 					mesh_chunk(
 						&mut self.mesher,
 						self.blocks.clone(),
@@ -109,7 +126,7 @@ impl ChunkRenderManager {
 					
 					*mesh = upload(&self.gl, &chunk.pos, &self.mesher.vertices, &self.quad_index);
 					
-					profiler_tree.leave();
+					drop(profiler_guard);
 				}
 				
 				if let ChunkMeshState::Meshed(mesh) = mesh {
@@ -120,8 +137,7 @@ impl ChunkRenderManager {
 				
 				let block_data = chunks.get_chunk_with_edges(chunk_pos).unwrap();
 				
-				let profiler_tree = common::profiler::profiler().get_current();
-				profiler_tree.enter_noguard("mesh-chunk");
+				let profiler_guard = common::profiler::enter("mesh-chunk");
 				
 				mesh_chunk(
 					&mut self.mesher,
@@ -137,9 +153,9 @@ impl ChunkRenderManager {
 					self.calls.push(mesh.draw_later());
 				}
 				
-				profiler_tree.leave();
+				drop(profiler_guard);
 				
-				self.chunks.insert(chunk_pos.clone(), (current_time_nanos(), mesh));
+				self.chunks.insert(chunk_pos.clone(), (Some(chunks.clock.now_nanos()), mesh));
 			}
 		}
 		
@@ -155,3 +171,26 @@ impl ChunkRenderManager {
 		self.gl.pop_debug();
 	}
 }
+
+/// Is a mesh built at the given time out of date for a chunk last changed at `last_update`?
+fn is_stale(meshed: Option<u64>, last_update: u64) -> bool {
+	match meshed {
+		Some(meshed) => last_update > meshed,
+		None => true,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::is_stale;
+	
+	#[test]
+	fn stale_meshes() {
+		assert!(!is_stale(Some(10), 0));
+		assert!(!is_stale(Some(10), 10));
+		assert!(is_stale(Some(10), 11));
+		
+		// A chunk that was never edited is meshed again after the atlas changed.
+		assert!(is_stale(None, 0));
+	}
+}
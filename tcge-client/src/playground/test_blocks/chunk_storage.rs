@@ -3,13 +3,17 @@ use super::*;
 pub struct ChunkStorage {
 	pub blocks: BlocksRef,
 	pub chunks: FxHashMap<ChunkCoord, Chunk>,
+	
+	/// The clock chunk updates are stamped with.
+	pub clock: Box<dyn Clock>,
 }
 
 impl ChunkStorage {
-	pub fn new(blocks: &BlocksRef) -> Self {
+	pub fn new(blocks: &BlocksRef, clock: Box<dyn Clock>) -> Self {
 		let mut storage = Self {
 			blocks: blocks.clone(),
-			chunks: FxHashMap::default()
+			chunks: FxHashMap::default(),
+			clock,
 		};
 		
 		let range: ChunkDim = 4;
@@ -261,9 +265,11 @@ impl ChunkStorage {
 		let cy = pos.y & CHUNK_SIZE_MASK_I;
 		let cz = pos.z & CHUNK_SIZE_MASK_I;
 		
+		let now = self.clock.now_nanos();
+		
 		let success = if let Some(chunk) = self.get_chunk_mut(&chunk_pos) {
 			match chunk.set_block(cx, cy, cz, state) {
-				Some(_) => true,
+				Some(_) => {chunk.last_update = now; true},
 				None    => false
 			}
 		} else {
@@ -271,7 +277,6 @@ impl ChunkStorage {
 		};
 		
 		if success {
-			let now = current_time_nanos();
 			if cx == 0 {
 				self.get_chunk_mut(&chunk_pos.add(-1,0,0))
 					.map(|c| {c.last_update = now});
@@ -1,4 +1,4 @@
-use common::current_time_nanos;
+use common::clock::Clock;
 use common::resources;
 
 
@@ -16,8 +16,8 @@ impl SolidColorMaterial {
 	
 	pub fn new(gl: &gl::Gl, res: &Resources) -> Result<Self, SolidColorMaterialError> {
 		
-		let shader_vert = ResourceLocation::from("core/shaders/solid-color.vert");
-		let shader_frag = ResourceLocation::from("core/shaders/solid-color.frag");
+		let shader_vert = ResourceLocation::from_static("core:shaders/solid-color.vert");
+		let shader_frag = ResourceLocation::from_static("core:shaders/solid-color.frag");
 		
 		let shader_vert = res.res_as_cstring(&shader_vert)
 			.map_err(SolidColorMaterialError::Resource)?;
@@ -1,5 +1,5 @@
 use rustc_hash::FxHashMap;
-use common::resources::{Resources, ResourceProvider, ResourceLocation};
+use common::resources::{Resources, ResourceProvider, ResourceLocation, ResourceError};
 use super::super::{ShaderObject, ProgramObject, UniformLocation, TextureObject};
 use super::TextRendererError;
 
@@ -18,12 +18,14 @@ impl TextRendererMaterial {
 		
 		gl.push_debug("Preparing text renderer shaders");
 		
-		let location = ResourceLocation::from_str("core/shaders/text");
+		let location = ResourceLocation::from_static("core:shaders/text");
 		
 		debug!("Loading font shader: {}", location);
 		
 		trace!("Loading vertex shader...");
-		let shader_vert = res.res_as_cstring(&location.add(".vert"))
+		let shader_vert = location.with_extension("vert")
+			.map_err(ResourceError::Location)
+			.and_then(|location| res.res_as_cstring(&location))
 			.map_err(TextRendererError::Resource)?;
 		
 		trace!("Compiling vertex shader...");
@@ -31,7 +33,9 @@ impl TextRendererMaterial {
 			.map_err(TextRendererError::Shader)?;
 		
 		trace!("Loading fragment shader...");
-		let shader_frag = res.res_as_cstring(&location.add(".frag"))
+		let shader_frag = location.with_extension("frag")
+			.map_err(ResourceError::Location)
+			.and_then(|location| res.res_as_cstring(&location))
 			.map_err(TextRendererError::Resource)?;
 		
 		trace!("Compiling fragment shader...");
@@ -24,8 +24,10 @@ pub fn new(gl: &gl::Gl, res: &Resources, name: &str) -> Result<TextRenderer, Tex
 	
 	gl.push_debug(&format!("Loading font: {}", name));
 	
-	let font_loc = ResourceLocation::from(format!("core/fonts/{}", name));
-	let index_loc = font_loc.add(&format!("/{}.fnt", name));
+	let font_loc = ResourceLocation::new("core", &format!("fonts/{}", name))
+		.map_err(|e| TextRendererError::Resource(ResourceError::Location(e)))?;
+	let index_loc = font_loc.join(&format!("{}.fnt", name))
+		.map_err(|e| TextRendererError::Resource(ResourceError::Location(e)))?;
 	
 	info!("Loading font: {} -> {}", font_loc, index_loc);
 	
@@ -1,5 +1,5 @@
 use std::io::{BufRead, BufReader};
-use common::resources::{ResourceLocation, Resources, ResourceProvider};
+use common::resources::{ResourceLocation, ResourceError, Resources, ResourceProvider};
 use super::{TextRenderer, TextRendererError, TextGlyph};
 use crate::render;
 
@@ -46,7 +46,8 @@ pub fn parse_file(
 				Ok(())
 			})?;
 			
-			let page_loc = ResourceLocation::from_string(font_loc.inner.clone() + "/" + &page_name);
+			let page_loc = font_loc.join(&page_name)
+				.map_err(|e| TextRendererError::Resource(ResourceError::Location(e)))?;
 			
 			text.gl.push_debug(&format!("Loading font-page {}", page_name));
 			debug!("Loading font page: {}", page_name);
@@ -138,12 +138,14 @@ impl WireframeMaterial {
 		
 		gl.push_debug("Preparing wireframe painter shaders");
 		
-		let location = ResourceLocation::from_str("core/shaders/wireframe");
+		let location = ResourceLocation::from_static("core:shaders/wireframe");
 		
 		debug!("Loading font shader: {}", location);
 		
 		trace!("Loading vertex shader...");
-		let shader_vert = res.res_as_cstring(&location.add(".vert"))
+		let shader_vert = location.with_extension("vert")
+			.map_err(ResourceError::Location)
+			.and_then(|location| res.res_as_cstring(&location))
 			.map_err(WireframeError::Resource)?;
 		
 		trace!("Compiling vertex shader...");
@@ -151,7 +153,9 @@ impl WireframeMaterial {
 			.map_err(WireframeError::Shader)?;
 		
 		trace!("Loading fragment shader...");
-		let shader_frag = res.res_as_cstring(&location.add(".frag"))
+		let shader_frag = location.with_extension("frag")
+			.map_err(ResourceError::Location)
+			.and_then(|location| res.res_as_cstring(&location))
 			.map_err(WireframeError::Resource)?;
 		
 		trace!("Compiling fragment shader...");
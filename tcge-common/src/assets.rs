@@ -0,0 +1,262 @@
+//! Typed loading and caching of assets, on top of the `Resources`.
+//!
+//! Every asset type has a single `AssetLoader` registered with the `Assets` cache,
+//! which turns a resource into a value that is shared by all `Handle`s to it.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::{Arc, Weak};
+use super::resources::{Resources, ResourceProvider, ResourceLocation, ResourceError};
+
+/// Turns the resource at a location into an asset.
+pub trait AssetLoader: 'static {
+	type Asset: 'static;
+	
+	fn load(&self, res: &Resources, location: &ResourceLocation) -> Result<Self::Asset, AssetErrorKind>;
+}
+
+/// A shared, reference-counted handle to a loaded asset.
+pub struct Handle<T> {
+	location: ResourceLocation,
+	inner: Arc<T>,
+}
+
+impl<T> Handle<T> {
+	pub fn location(&self) -> &ResourceLocation {
+		&self.location
+	}
+}
+
+impl<T> Clone for Handle<T> {
+	fn clone(&self) -> Self {
+		Self {
+			location: self.location.clone(),
+			inner: self.inner.clone(),
+		}
+	}
+}
+
+impl<T> std::ops::Deref for Handle<T> {
+	type Target = T;
+	
+	fn deref(&self) -> &Self::Target {
+		&self.inner
+	}
+}
+
+/// A cache of assets, keyed by type and location.
+///
+/// Assets are only kept alive by their handles;
+/// loading an asset that still has a handle returns the same asset.
+pub struct Assets {
+	/// Boxed `Box<dyn AssetLoader<Asset = T>>`'s, by the `TypeId` of `T`.
+	loaders: HashMap<TypeId, Box<dyn Any>>,
+	
+	/// `Weak<T>`'s, by the `TypeId` of `T` and location.
+	cache: HashMap<(TypeId, ResourceLocation), Box<dyn Cached>>,
+}
+
+impl Assets {
+	/// Creates a new cache with the loaders for `String` and `Vec<u8>`.
+	pub fn new() -> Self {
+		let mut new = Self {
+			loaders: HashMap::new(),
+			cache: HashMap::new(),
+		};
+		
+		new.register_loader(StringLoader);
+		new.register_loader(BytesLoader);
+		new
+	}
+	
+	/// Registers the loader for its asset type, replacing any previous one.
+	pub fn register_loader<L: AssetLoader>(&mut self, loader: L) {
+		let loader: Box<dyn AssetLoader<Asset = L::Asset>> = Box::new(loader);
+		self.loaders.insert(TypeId::of::<L::Asset>(), Box::new(loader));
+	}
+	
+	/// Returns a handle to the asset at the given location, loading it if necessary.
+	pub fn load<T: 'static>(&mut self, res: &Resources, location: &ResourceLocation) -> Result<Handle<T>, AssetError> {
+		let key = (TypeId::of::<T>(), location.clone());
+		
+		if let Some(handle) = self.get_cached(&key) {
+			return Ok(handle);
+		}
+		
+		let loader = self.loaders.get(&key.0)
+			.and_then(|loader| loader.downcast_ref::<Box<dyn AssetLoader<Asset = T>>>())
+			.ok_or_else(|| AssetError {
+				location: location.clone(),
+				kind: AssetErrorKind::NoLoader(std::any::type_name::<T>()),
+			})?;
+		
+		debug!("Loading asset: {}", location);
+		let asset = loader.load(res, location)
+			.map_err(|kind| AssetError {
+				location: location.clone(),
+				kind
+			})?;
+		
+		let inner = Arc::new(asset);
+		self.cache.insert(key, Box::new(Arc::downgrade(&inner)));
+		
+		Ok(Handle {
+			location: location.clone(),
+			inner
+		})
+	}
+	
+	fn get_cached<T: 'static>(&self, key: &(TypeId, ResourceLocation)) -> Option<Handle<T>> {
+		let inner = self.cache.get(key)?
+			.as_any()
+			.downcast_ref::<Weak<T>>()?
+			.upgrade()?;
+		
+		Some(Handle {
+			location: key.1.clone(),
+			inner
+		})
+	}
+	
+	/// Forgets all assets that no longer have any handles.
+	///
+	/// Returns the number of forgotten assets.
+	pub fn collect_garbage(&mut self) -> usize {
+		let before = self.cache.len();
+		self.cache.retain(|_, weak| !weak.is_dead());
+		before - self.cache.len()
+	}
+	
+	/// Forgets the asset at the given location, regardless of its type,
+	/// so that the next `load` reads it again.
+	///
+	/// Existing handles keep the old asset.
+	pub fn invalidate(&mut self, location: &ResourceLocation) {
+		self.cache.retain(|(_, cached), _| cached != location);
+	}
+}
+
+impl Default for Assets {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// A type-erased `Weak<T>` in the cache.
+trait Cached {
+	fn is_dead(&self) -> bool;
+	fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: 'static> Cached for Weak<T> {
+	fn is_dead(&self) -> bool {
+		self.strong_count() == 0
+	}
+	
+	fn as_any(&self) -> &dyn Any {
+		self
+	}
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Loads a resource as UTF-8 string.
+pub struct StringLoader;
+impl AssetLoader for StringLoader {
+	type Asset = String;
+	
+	fn load(&self, res: &Resources, location: &ResourceLocation) -> Result<String, AssetErrorKind> {
+		res.res_as_string(location).map_err(AssetErrorKind::Resource)
+	}
+}
+
+/// Loads a resource as raw bytes.
+pub struct BytesLoader;
+impl AssetLoader for BytesLoader {
+	type Asset = Vec<u8>;
+	
+	fn load(&self, res: &Resources, location: &ResourceLocation) -> Result<Vec<u8>, AssetErrorKind> {
+		res.res_as_buffer(location).map_err(AssetErrorKind::Resource)
+	}
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// An error that occurred while loading the asset at `location`.
+#[derive(Debug)]
+pub struct AssetError {
+	pub location: ResourceLocation,
+	pub kind: AssetErrorKind,
+}
+
+/// The reason an asset could not be loaded.
+#[derive(Debug)]
+pub enum AssetErrorKind {
+	/// No loader is registered for the asset type.
+	NoLoader(&'static str),
+	
+	/// The resource could not be read.
+	Resource(ResourceError),
+	
+	/// The resource could not be parsed.
+	Parse(String),
+}
+
+impl std::fmt::Display for AssetError {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+		match &self.kind {
+			AssetErrorKind::NoLoader(ty) => write!(f, "Failed to load {}: No loader for {}", self.location, ty),
+			AssetErrorKind::Resource(err) => write!(f, "Failed to load {}: {:?}", self.location, err),
+			AssetErrorKind::Parse(err) => write!(f, "Failed to parse {}: {}", self.location, err),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::resources::IncludeProvider;
+	
+	struct LengthLoader;
+	impl AssetLoader for LengthLoader {
+		type Asset = usize;
+		
+		fn load(&self, res: &Resources, location: &ResourceLocation) -> Result<usize, AssetErrorKind> {
+			let text = res.res_as_string(location).map_err(AssetErrorKind::Resource)?;
+			text.trim().parse().map_err(|e: std::num::ParseIntError| AssetErrorKind::Parse(e.to_string()))
+		}
+	}
+	
+	#[test]
+	fn load_and_deduplicate() {
+		let mut res = Resources::empty();
+		res.register_provider_by_type(IncludeProvider::new(vec![
+			("core/number.txt", b"42"),
+			("core/text.txt", b"hello"),
+		]));
+		
+		let mut assets = Assets::new();
+		assets.register_loader(LengthLoader);
+		
+		let number = ResourceLocation::from_static("core:number.txt");
+		let a = assets.load::<usize>(&res, &number).unwrap();
+		let b = assets.load::<usize>(&res, &number).unwrap();
+		assert_eq!(*a, 42);
+		assert!(Arc::ptr_eq(&a.inner, &b.inner));
+		
+		// The same location can be loaded as a different type.
+		let s = assets.load::<String>(&res, &number).unwrap();
+		assert_eq!(s.as_str(), "42");
+		
+		drop((a, b, s));
+		assert_eq!(assets.collect_garbage(), 2);
+		
+		let text = ResourceLocation::from_static("core:text.txt");
+		let err = assets.load::<usize>(&res, &text).err().unwrap();
+		assert_eq!(err.location, text);
+		assert!(matches!(err.kind, AssetErrorKind::Parse(_)));
+		
+		let err = assets.load::<f32>(&res, &text).err().unwrap();
+		assert!(matches!(err.kind, AssetErrorKind::NoLoader(_)));
+	}
+}
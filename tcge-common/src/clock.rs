@@ -0,0 +1,102 @@
+//! Sources of time for the engine.
+//!
+//! Anything that needs to know 'when' should ask a `Clock` it was given,
+//! instead of reading the wall-clock itself, so that it can be driven by a `ManualClock` in tests.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A monotonic source of time.
+pub trait Clock {
+	/// Returns the current time in nanoseconds, relative to an arbitrary but fixed epoch.
+	fn now_nanos(&self) -> u64;
+	
+	/// Returns the current time in seconds, relative to the same epoch as `now_nanos`.
+	fn now_seconds(&self) -> f64 {
+		self.now_nanos() as f64 / 1_000_000_000.0
+	}
+}
+
+/// Closures returning the current time in seconds, such as `glfw.get_time()`.
+impl<F: Fn() -> f64> Clock for F {
+	fn now_nanos(&self) -> u64 {
+		(self() * 1_000_000_000.0) as u64
+	}
+	
+	fn now_seconds(&self) -> f64 {
+		self()
+	}
+}
+
+/// The precise, monotonic clock of the operating system.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct RealClock;
+
+impl RealClock {
+	pub fn new() -> Self {
+		RealClock
+	}
+}
+
+impl Clock for RealClock {
+	fn now_nanos(&self) -> u64 {
+		crate::current_time_nanos_precise()
+	}
+}
+
+/// A clock that only moves when told to.
+///
+/// Clones share the same time, so one can be handed out while the other is advanced.
+#[derive(Clone, Default, Debug)]
+pub struct ManualClock {
+	nanos: Arc<AtomicU64>,
+}
+
+impl ManualClock {
+	/// Creates a clock standing at zero.
+	pub fn new() -> Self {
+		Self::default()
+	}
+	
+	/// Sets the time to the given number of nanoseconds.
+	pub fn set_nanos(&self, nanos: u64) {
+		self.nanos.store(nanos, Ordering::SeqCst);
+	}
+	
+	/// Moves the time forward by the given number of nanoseconds.
+	pub fn advance_nanos(&self, nanos: u64) {
+		self.nanos.fetch_add(nanos, Ordering::SeqCst);
+	}
+	
+	/// Moves the time forward by the given number of seconds.
+	pub fn advance_seconds(&self, seconds: f64) {
+		self.advance_nanos((seconds * 1_000_000_000.0) as u64);
+	}
+}
+
+impl Clock for ManualClock {
+	fn now_nanos(&self) -> u64 {
+		self.nanos.load(Ordering::SeqCst)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn manual_clock_is_shared() {
+		let clock = ManualClock::new();
+		let handed_out = clock.clone();
+		
+		clock.advance_nanos(250_000_000);
+		assert_eq!(handed_out.now_nanos(), 250_000_000);
+		assert_eq!(handed_out.now_seconds(), 0.25);
+		
+		clock.set_nanos(3_000_000_000);
+		assert_eq!(handed_out.now_seconds(), 3.0);
+		
+		let closure = || 1.5;
+		assert_eq!(closure.now_nanos(), 1_500_000_000);
+	}
+}
@@ -1,32 +1,96 @@
-use std::sync::mpsc;
+use std::sync::{mpsc, Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::thread;
-use std::io::stdin;
+use std::path::PathBuf;
+use std::io::{self, BufRead, BufReader, Write};
 
-type Message = String;
+use rustyline::Editor;
+use rustyline::error::ReadlineError;
 
+use super::commands::{Completer, ResponseSink};
+
+/// A line entered into a console, and where its responses go.
+pub struct Input {
+	pub line: String,
+	pub sink: ResponseSink,
+}
+
+type Message = Input;
+
+/// The maximum number of lines kept in the history.
+pub const HISTORY_SIZE: usize = 1000;
+
+/// The queue of commands from stdin and all remote consoles.
 pub struct CommandLine {
-	pub handle: thread::JoinHandle<()>,
+	pub handles: Vec<thread::JoinHandle<()>>,
 	pub pipe: mpsc::Receiver<Message>,
+	sender: mpsc::Sender<Message>,
 }
 
 impl CommandLine {
-	pub fn new() -> Self {
-		let (pipe, recv) = mpsc::channel();
+	/// Starts reading commands from stdin, with line editing and completion.
+	///
+	/// If a history file is given, the history is loaded from and saved to it.
+	pub fn new(completer: Completer, history: Option<PathBuf>) -> Self {
+		let mut new = Self::without_stdin();
+		let pipe = new.sender.clone();
 		
 		let thread = thread::Builder::new()
 			.name("CMD-Reader".to_string())
 			.spawn(move || {
 				let pipe = pipe;
-				work(pipe);
+				work(pipe, completer, history);
 			})
 			.unwrap();
 		
+		new.handles.push(thread);
+		new
+	}
+	
+	/// Creates a command line that only receives commands from remote consoles.
+	pub fn without_stdin() -> Self {
+		let (sender, pipe) = mpsc::channel();
+		
 		Self {
-			handle: thread,
-			pipe: recv
+			handles: vec![],
+			pipe,
+			sender,
 		}
 	}
 	
+	/// Accepts remote consoles at the given address, until the process ends.
+	///
+	/// Every line a remote console sends is queued as a command,
+	/// while the given `Remotes` stream output back to it.
+	/// Returns a description of the address actually listened on.
+	pub fn listen(&mut self, address: &RemoteAddr, remotes: Remotes) -> io::Result<String> {
+		let (listener, description) = Listener::bind(address)?;
+		info!("Listening for remote consoles on {}", description);
+		
+		let pipe = self.sender.clone();
+		let thread = thread::Builder::new()
+			.name("CMD-Listener".to_string())
+			.spawn(move || {
+				loop {
+					match listener.accept() {
+						Ok((reader, writer)) => remotes.connect(reader, writer, pipe.clone()),
+						Err(e) => {
+							error!("Failed to accept remote console: {}", e);
+							break;
+						}
+					}
+				}
+			})?;
+		
+		self.handles.push(thread);
+		Ok(description)
+	}
+	
+	/// Returns a sender that queues commands as if they were entered into this command line.
+	pub fn sender(&self) -> mpsc::Sender<Message> {
+		self.sender.clone()
+	}
+	
 	pub fn recv(&self) -> Option<Message> {
 		let recv = self.pipe.try_recv();
 		
@@ -48,18 +112,317 @@ impl CommandLine {
 	}
 }
 
-pub fn work(pipe: mpsc::Sender<Message>) {
-	let stdin = stdin();
-	let mut buffer = String::new();
+pub fn work(pipe: mpsc::Sender<Message>, completer: Completer, history: Option<PathBuf>) {
+	let config = rustyline::Config::builder()
+		.max_history_size(HISTORY_SIZE)
+		.history_ignore_dups(true)
+		.history_ignore_space(true)
+		.auto_add_history(false)
+		.build();
+	
+	let mut editor = Editor::<LineHelper>::with_config(config);
+	editor.set_helper(Some(LineHelper {completer}));
+	
+	if let Some(history) = history.as_ref() {
+		// A missing history file is expected on the first start.
+		if history.exists() {
+			if let Err(e) = editor.load_history(history) {
+				warn!("Unable to load command history from {}: {}", history.display(), e);
+			}
+		}
+	}
 	
-	while let Ok(_) = stdin.read_line(&mut buffer) {
-		let command = buffer.trim().to_string();
+	loop {
+		let command = match editor.readline("") {
+			Ok(line) => line.trim().to_string(),
+			
+			// Ctrl-C used to kill the process; now it asks the application to stop.
+			Err(ReadlineError::Interrupted) => "stop".to_string(),
+			
+			Err(ReadlineError::Eof) => break,
+			
+			Err(e) => {
+				error!("Unable to read command: {}", e);
+				break;
+			}
+		};
+		
+		if command.is_empty() {
+			continue;
+		}
+		
+		editor.add_history_entry(command.as_str());
 		
-		if let Err(e) = pipe.send(command) {
+		if let Some(history) = history.as_ref() {
+			if let Err(e) = editor.save_history(history) {
+				warn!("Unable to save command history to {}: {}", history.display(), e);
+			}
+		}
+		
+		let input = Input {
+			line: command,
+			sink: ResponseSink::log(),
+		};
+		
+		if let Err(e) = pipe.send(input) {
 			error!("Unable to send command to main-thread: {}", e);
 			break;
 		}
+	}
+}
+
+/// Connects the `Completer` of the command registry to the line editor.
+#[derive(rustyline_derive::Helper, rustyline_derive::Hinter, rustyline_derive::Highlighter, rustyline_derive::Validator)]
+struct LineHelper {
+	completer: Completer,
+}
+
+impl rustyline::completion::Completer for LineHelper {
+	type Candidate = String;
+	
+	fn complete(&self, line: &str, pos: usize, _ctx: &rustyline::Context<'_>) -> rustyline::Result<(usize, Vec<String>)> {
+		Ok(self.completer.complete(&line[..pos]))
+	}
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Where remote consoles can connect to.
+#[derive(Clone, PartialEq, Debug)]
+pub enum RemoteAddr {
+	/// A TCP port on localhost; `0` lets the OS pick one.
+	Tcp(u16),
+	
+	/// A Unix domain socket at the given path.
+	#[cfg(unix)]
+	Unix(PathBuf),
+}
+
+impl std::str::FromStr for RemoteAddr {
+	type Err = String;
+	
+	/// Parses `tcp:<port>` or `unix:<path>`.
+	fn from_str(address: &str) -> Result<Self, Self::Err> {
+		if let Some(port) = address.strip_prefix("tcp:") {
+			return port.parse()
+				.map(RemoteAddr::Tcp)
+				.map_err(|e| format!("Invalid port '{}': {}", port, e));
+		}
+		
+		#[cfg(unix)]
+		{
+			if let Some(path) = address.strip_prefix("unix:") {
+				return Ok(RemoteAddr::Unix(PathBuf::from(path)));
+			}
+		}
+		
+		Err(format!("Invalid console address '{}', expected tcp:<port> or unix:<path>", address))
+	}
+}
+
+enum Listener {
+	Tcp(std::net::TcpListener),
+	#[cfg(unix)]
+	Unix(std::os::unix::net::UnixListener),
+}
+
+type Connection = (Box<dyn io::Read + Send>, Box<dyn Write + Send>);
+
+impl Listener {
+	fn bind(address: &RemoteAddr) -> io::Result<(Self, String)> {
+		match address {
+			RemoteAddr::Tcp(port) => {
+				// Only local tools may drive the application.
+				let listener = std::net::TcpListener::bind(("127.0.0.1", *port))?;
+				let description = format!("tcp:{}", listener.local_addr()?.port());
+				Ok((Listener::Tcp(listener), description))
+			},
+			
+			#[cfg(unix)]
+			RemoteAddr::Unix(path) => {
+				// A stale socket of a previous run would make binding fail.
+				if path.exists() {
+					std::fs::remove_file(path)?;
+				}
+				
+				let listener = std::os::unix::net::UnixListener::bind(path)?;
+				Ok((Listener::Unix(listener), format!("unix:{}", path.display())))
+			},
+		}
+	}
+	
+	fn accept(&self) -> io::Result<Connection> {
+		match self {
+			Listener::Tcp(listener) => {
+				let (stream, _) = listener.accept()?;
+				Ok((Box::new(stream.try_clone()?), Box::new(stream)))
+			},
+			
+			#[cfg(unix)]
+			Listener::Unix(listener) => {
+				let (stream, _) = listener.accept()?;
+				Ok((Box::new(stream.try_clone()?), Box::new(stream)))
+			},
+		}
+	}
+}
+
+/// The output streams of the connected remote consoles, by their id.
+type Consoles = Vec<(usize, Box<dyn Write + Send>)>;
+
+/// The connected remote consoles, which output is streamed to.
+#[derive(Clone, Default)]
+pub struct Remotes {
+	consoles: Arc<Mutex<Consoles>>,
+	next_id: Arc<AtomicUsize>,
+}
+
+impl Remotes {
+	pub fn new() -> Self {
+		Self::default()
+	}
+	
+	/// Returns the number of connected consoles.
+	pub fn count(&self) -> usize {
+		self.consoles.lock().map(|consoles| consoles.len()).unwrap_or(0)
+	}
+	
+	/// Sends a line to every connected console, forgetting the ones that went away.
+	///
+	/// This must not log anything, since it is called by the `RemoteLogger`.
+	pub fn broadcast(&self, line: &str) {
+		let mut consoles = match self.consoles.lock() {
+			Ok(consoles) => consoles,
+			Err(_) => return,
+		};
+		
+		consoles.retain_mut(|(_, out)| {
+			writeln!(out, "{}", line).and_then(|_| out.flush()).is_ok()
+		});
+	}
+	
+	/// Sends a line to the console with the given id, if it is still connected.
+	pub fn send_to(&self, id: usize, line: &str) {
+		let mut consoles = match self.consoles.lock() {
+			Ok(consoles) => consoles,
+			Err(_) => return,
+		};
+		
+		consoles.retain_mut(|(console, out)| {
+			*console != id || writeln!(out, "{}", line).and_then(|_| out.flush()).is_ok()
+		});
+	}
+	
+	fn connect(&self, reader: Box<dyn io::Read + Send>, writer: Box<dyn Write + Send>, pipe: mpsc::Sender<Message>) {
+		let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+		
+		if let Ok(mut consoles) = self.consoles.lock() {
+			consoles.push((id, writer));
+		}
+		
+		info!("Remote console #{} connected.", id);
+		
+		let remotes = self.clone();
+		let sink_remotes = self.clone();
+		let sink = ResponseSink::new(move |response| {
+			sink_remotes.send_to(id, &response.to_string());
+		});
+		
+		let thread = thread::Builder::new()
+			.name(format!("CMD-Remote {}", id))
+			.spawn(move || {
+				for line in BufReader::new(reader).lines() {
+					let command = match line {
+						Ok(line) => line.trim().to_string(),
+						Err(_) => break,
+					};
+					
+					if command.is_empty() {
+						continue;
+					}
+					
+					let input = Input {
+						line: command,
+						sink: sink.clone(),
+					};
+					
+					if pipe.send(input).is_err() {
+						break;
+					}
+				}
+				
+				remotes.disconnect(id);
+			});
+		
+		if let Err(e) = thread {
+			error!("Failed to spawn thread for remote console #{}: {}", id, e);
+			self.disconnect(id);
+		}
+	}
+	
+	fn disconnect(&self, id: usize) {
+		if let Ok(mut consoles) = self.consoles.lock() {
+			consoles.retain(|(console, _)| *console != id);
+		}
+		
+		info!("Remote console #{} disconnected.", id);
+	}
+}
+
+/// A logger that streams log output to all remote consoles.
+pub struct RemoteLogger {
+	pub remotes: Remotes,
+	pub level: log::LevelFilter,
+}
+
+impl log::Log for RemoteLogger {
+	fn enabled(&self, metadata: &log::Metadata) -> bool {
+		metadata.level() <= self.level
+	}
+	
+	fn log(&self, record: &log::Record) {
+		if self.enabled(record.metadata()) {
+			self.remotes.broadcast(&format!("[{}] {}", record.level(), record.args()));
+		}
+	}
+	
+	fn flush(&self) {}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::time::Duration;
+	
+	#[test]
+	fn remote_console() {
+		let remotes = Remotes::new();
+		let mut command_line = CommandLine::without_stdin();
+		let port = match command_line.listen(&RemoteAddr::Tcp(0), remotes.clone()).unwrap().parse() {
+			Ok(RemoteAddr::Tcp(port)) => port,
+			_ => panic!("not listening on tcp"),
+		};
+		
+		let mut client = std::net::TcpStream::connect(("127.0.0.1", port)).unwrap();
+		client.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+		client.write_all(b"echo hello\n\nset-tps 30\n").unwrap();
+		
+		let received: Vec<Input> = command_line.pipe.iter().take(2).collect();
+		assert_eq!(received[0].line, "echo hello");
+		assert_eq!(received[1].line, "set-tps 30");
+		
+		// The connection is registered before its first line is read.
+		assert_eq!(remotes.count(), 1);
+		remotes.broadcast("log line");
+		received[1].sink.success("Changed tick-rate");
+		
+		let mut client = BufReader::new(&client);
+		let mut response = String::new();
+		client.read_line(&mut response).unwrap();
+		assert_eq!(response, "log line\n");
 		
-		buffer.clear();
+		response.clear();
+		client.read_line(&mut response).unwrap();
+		assert_eq!(response, "OK Changed tick-rate\n");
 	}
 }
@@ -0,0 +1,824 @@
+//! A registry of console commands, their typed arguments and help texts.
+//!
+//! The registry only parses command lines into `Invocation`s;
+//! executing them is up to whoever owns the command, usually a backbone node.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+
+/// The type of a command argument.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ArgKind {
+	/// A signed integer.
+	Int,
+	
+	/// A floating point number.
+	Float,
+	
+	/// A location in the backbone, like `/playground`.
+	Path,
+	
+	/// The name of a registered block.
+	Block,
+	
+	/// A block coordinate, given as three integers.
+	Coord,
+	
+	/// The name of a registered command.
+	Command,
+	
+	/// A single word.
+	Word,
+	
+	/// The rest of the line; must be the last argument.
+	Text,
+}
+
+impl std::fmt::Display for ArgKind {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+		write!(f, "{}", match self {
+			ArgKind::Int => "integer",
+			ArgKind::Float => "number",
+			ArgKind::Path => "path",
+			ArgKind::Block => "block name",
+			ArgKind::Coord => "coordinate",
+			ArgKind::Command => "command",
+			ArgKind::Word => "word",
+			ArgKind::Text => "text",
+		})
+	}
+}
+
+/// The declaration of a single command argument.
+#[derive(Clone, Debug)]
+pub struct ArgSpec {
+	pub name: &'static str,
+	pub kind: ArgKind,
+	pub optional: bool,
+}
+
+/// The declaration of a command.
+#[derive(Clone, Debug)]
+pub struct Command {
+	name: &'static str,
+	aliases: Vec<&'static str>,
+	args: Vec<ArgSpec>,
+	help: &'static str,
+}
+
+impl Command {
+	/// Creates a command without arguments.
+	pub fn new(name: &'static str, help: &'static str) -> Self {
+		Self {
+			name,
+			aliases: vec![],
+			args: vec![],
+			help,
+		}
+	}
+	
+	/// Adds another name the command can be invoked by.
+	pub fn alias(mut self, alias: &'static str) -> Self {
+		self.aliases.push(alias);
+		self
+	}
+	
+	/// Adds a required argument.
+	pub fn arg(mut self, name: &'static str, kind: ArgKind) -> Self {
+		debug_assert!(self.args.iter().all(|arg| !arg.optional), "required arguments must come before optional ones");
+		self.args.push(ArgSpec {name, kind, optional: false});
+		self
+	}
+	
+	/// Adds an optional argument; all following arguments must be optional too.
+	pub fn optional(mut self, name: &'static str, kind: ArgKind) -> Self {
+		self.args.push(ArgSpec {name, kind, optional: true});
+		self
+	}
+	
+	pub fn get_name(&self) -> &'static str {
+		self.name
+	}
+	
+	pub fn get_aliases(&self) -> &[&'static str] {
+		&self.aliases
+	}
+	
+	pub fn get_args(&self) -> &[ArgSpec] {
+		&self.args
+	}
+	
+	pub fn get_help(&self) -> &'static str {
+		self.help
+	}
+	
+	/// Returns whether the command is invoked by the given name.
+	pub fn is_named(&self, name: &str) -> bool {
+		self.name == name || self.aliases.contains(&name)
+	}
+	
+	/// Returns the usage line of the command, like `set-tps [rate]`.
+	pub fn usage(&self) -> String {
+		let mut usage = self.name.to_string();
+		
+		for arg in self.args.iter() {
+			if arg.optional {
+				usage += &format!(" [{}]", arg.name);
+			} else {
+				usage += &format!(" <{}>", arg.name);
+			}
+		}
+		
+		usage
+	}
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// The parsed value of an argument.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ArgValue {
+	Int(i64),
+	Float(f64),
+	Path(String),
+	Block(String),
+	Coord(i64, i64, i64),
+	Word(String),
+	Text(String),
+}
+
+/// A successfully parsed command line.
+#[derive(Clone, Debug)]
+pub struct Invocation {
+	name: &'static str,
+	args: Vec<(&'static str, ArgValue)>,
+}
+
+impl Invocation {
+	/// Returns the name of the invoked command, even if it was invoked by an alias.
+	pub fn get_name(&self) -> &'static str {
+		self.name
+	}
+	
+	/// Returns the value of the given argument, if it was given.
+	pub fn get(&self, name: &str) -> Option<&ArgValue> {
+		self.args.iter()
+			.find(|(arg, _)| *arg == name)
+			.map(|(_, value)| value)
+	}
+	
+	pub fn get_int(&self, name: &str) -> Option<i64> {
+		match self.get(name)? {
+			ArgValue::Int(value) => Some(*value),
+			_ => None
+		}
+	}
+	
+	/// Returns the value of a float argument; integers are accepted as well.
+	pub fn get_float(&self, name: &str) -> Option<f64> {
+		match self.get(name)? {
+			ArgValue::Float(value) => Some(*value),
+			ArgValue::Int(value) => Some(*value as f64),
+			_ => None
+		}
+	}
+	
+	pub fn get_coord(&self, name: &str) -> Option<(i64, i64, i64)> {
+		match self.get(name)? {
+			ArgValue::Coord(x, y, z) => Some((*x, *y, *z)),
+			_ => None
+		}
+	}
+	
+	/// Returns the value of a path, block, word or text argument.
+	pub fn get_str(&self, name: &str) -> Option<&str> {
+		match self.get(name)? {
+			ArgValue::Path(value)
+			| ArgValue::Block(value)
+			| ArgValue::Word(value)
+			| ArgValue::Text(value) => Some(value.as_str()),
+			_ => None
+		}
+	}
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A command and who registered it.
+struct Registered {
+	scope: String,
+	command: Command,
+}
+
+/// The set of all currently available commands.
+pub struct CommandRegistry {
+	commands: Vec<Registered>,
+	
+	/// Commands replaced by another scope, restored once that scope is gone.
+	shadowed: Vec<Registered>,
+	
+	/// The known values of argument kinds, like block names.
+	vocabulary: HashMap<ArgKind, Vec<String>>,
+	
+	/// A copy of the commands and vocabulary for other threads.
+	completer: Completer,
+}
+
+/// The scope of commands that are always available.
+pub const GLOBAL_SCOPE: &str = "";
+
+impl CommandRegistry {
+	/// Creates a registry containing only the `help` command.
+	pub fn new() -> Self {
+		let mut new = Self {
+			commands: vec![],
+			shadowed: vec![],
+			vocabulary: HashMap::new(),
+			completer: Completer::default(),
+		};
+		
+		new.register(Command::new("help", "Lists all commands, or explains the given one.")
+			.alias("?")
+			.optional("command", ArgKind::Command)
+		);
+		
+		new
+	}
+	
+	/// Registers a command that is always available.
+	pub fn register(&mut self, command: Command) {
+		self.register_scoped(GLOBAL_SCOPE, command);
+	}
+	
+	/// Registers a command owned by the given scope, usually the path of a backbone node.
+	///
+	/// Replaces any command with the same name.
+	/// If it belongs to another scope, it comes back when this scope is unregistered.
+	pub fn register_scoped(&mut self, scope: &str, command: Command) {
+		if let Some(index) = self.commands.iter().position(|reg| reg.command.name == command.name) {
+			let replaced = self.commands.remove(index);
+			
+			if replaced.scope != scope {
+				warn!("Command '{}' of '{}' is shadowed by '{}'.", command.name, replaced.scope, scope);
+				self.shadowed.push(replaced);
+			} else {
+				warn!("Command '{}' was registered again by '{}'.", command.name, scope);
+			}
+		}
+		
+		self.commands.push(Registered {
+			scope: scope.to_string(),
+			command
+		});
+		
+		self.publish();
+	}
+	
+	/// Removes all commands owned by the given scope, restoring the commands they shadowed.
+	pub fn unregister_scope(&mut self, scope: &str) {
+		self.shadowed.retain(|reg| reg.scope != scope);
+		
+		let (removed, kept): (Vec<Registered>, Vec<Registered>) = self.commands.drain(..)
+			.partition(|reg| reg.scope == scope);
+		self.commands = kept;
+		
+		for reg in removed {
+			if let Some(index) = self.shadowed.iter().rposition(|shadowed| shadowed.command.name == reg.command.name) {
+				let restored = self.shadowed.remove(index);
+				self.commands.push(restored);
+			}
+		}
+		
+		self.publish();
+	}
+	
+	/// Sets the known values of the given argument kind, used for completion.
+	///
+	/// Block arguments only accept known names, unless there are none.
+	pub fn set_vocabulary(&mut self, kind: ArgKind, words: Vec<String>) {
+		self.vocabulary.insert(kind, words);
+		self.publish();
+	}
+	
+	pub fn get_vocabulary(&self, kind: ArgKind) -> &[String] {
+		self.vocabulary.get(&kind)
+			.map(|words| words.as_slice())
+			.unwrap_or(&[])
+	}
+	
+	/// Returns a handle that completes command lines, even on other threads.
+	///
+	/// It follows all later changes to the registry.
+	pub fn completer(&self) -> Completer {
+		self.completer.clone()
+	}
+	
+	fn publish(&self) {
+		let mut vocabulary = self.vocabulary.clone();
+		vocabulary.insert(ArgKind::Command, self.get_commands()
+			.flat_map(|command| std::iter::once(&command.name).chain(command.aliases.iter()))
+			.map(|name| name.to_string())
+			.collect()
+		);
+		
+		if let Ok(mut known) = self.completer.known.write() {
+			known.commands = self.get_commands().cloned().collect();
+			known.vocabulary = vocabulary;
+		}
+	}
+	
+	/// Returns all commands, in order of registration.
+	pub fn get_commands(&self) -> impl Iterator<Item = &Command> {
+		self.commands.iter().map(|reg| &reg.command)
+	}
+	
+	/// Returns the command invoked by the given name or alias.
+	pub fn find(&self, name: &str) -> Option<&Command> {
+		self.get_commands().find(|command| command.is_named(name))
+	}
+	
+	/// Returns the help text for all commands, or the given one, line by line.
+	pub fn help(&self, command: Option<&str>) -> Result<Vec<String>, CommandError> {
+		if let Some(name) = command {
+			let command = self.find(name)
+				.ok_or_else(|| CommandError::UnknownCommand(name.to_string()))?;
+			
+			let mut lines = vec![
+				format!("{} - {}", command.usage(), command.help)
+			];
+			
+			if !command.aliases.is_empty() {
+				lines.push(format!("  aliases: {}", command.aliases.join(", ")));
+			}
+			
+			for arg in command.args.iter() {
+				lines.push(format!("  {}: {}", arg.name, arg.kind));
+			}
+			
+			return Ok(lines);
+		}
+		
+		let mut commands: Vec<&Command> = self.get_commands().collect();
+		commands.sort_by_key(|command| command.name);
+		
+		Ok(commands.iter()
+			.map(|command| format!("{} - {}", command.usage(), command.help))
+			.collect())
+	}
+	
+	/// Parses the given line into an invocation of a registered command.
+	pub fn parse(&self, line: &str) -> Result<Invocation, CommandError> {
+		let line = line.trim();
+		
+		let (name, mut rest) = match line.find(char::is_whitespace) {
+			Some(mid) => (&line[..mid], line[mid..].trim_start()),
+			None => (line, ""),
+		};
+		
+		if name.is_empty() {
+			return Err(CommandError::Empty);
+		}
+		
+		let command = self.find(name)
+			.ok_or_else(|| CommandError::UnknownCommand(name.to_string()))?;
+		
+		let mut args = Vec::with_capacity(command.args.len());
+		
+		for spec in command.args.iter() {
+			if rest.is_empty() {
+				if spec.optional {
+					break;
+				}
+				
+				return Err(CommandError::MissingArgument {
+					command: command.name,
+					argument: spec.name,
+				});
+			}
+			
+			let (value, remaining) = self.parse_arg(command, spec, rest)?;
+			args.push((spec.name, value));
+			rest = remaining;
+		}
+		
+		if !rest.is_empty() {
+			return Err(CommandError::TooManyArguments {
+				command: command.name,
+				extra: rest.to_string(),
+			});
+		}
+		
+		Ok(Invocation {
+			name: command.name,
+			args
+		})
+	}
+	
+	fn parse_arg<'l>(&self, command: &Command, spec: &ArgSpec, rest: &'l str) -> Result<(ArgValue, &'l str), CommandError> {
+		let invalid = |value: &str| CommandError::InvalidArgument {
+			command: command.name,
+			argument: spec.name,
+			expected: spec.kind,
+			value: value.to_string(),
+		};
+		
+		if spec.kind == ArgKind::Text {
+			return Ok((ArgValue::Text(rest.to_string()), ""));
+		}
+		
+		if spec.kind == ArgKind::Coord {
+			let mut rest = rest;
+			let mut xyz = [0; 3];
+			
+			for value in xyz.iter_mut() {
+				let (word, remaining) = next_word(rest);
+				
+				if word.is_empty() {
+					return Err(CommandError::MissingArgument {
+						command: command.name,
+						argument: spec.name,
+					});
+				}
+				
+				*value = word.parse().map_err(|_| invalid(word))?;
+				rest = remaining;
+			}
+			
+			return Ok((ArgValue::Coord(xyz[0], xyz[1], xyz[2]), rest));
+		}
+		
+		let (word, rest) = next_word(rest);
+		
+		let value = match spec.kind {
+			ArgKind::Int => ArgValue::Int(word.parse().map_err(|_| invalid(word))?),
+			ArgKind::Float => ArgValue::Float(word.parse().map_err(|_| invalid(word))?),
+			ArgKind::Path => ArgValue::Path(word.to_string()),
+			ArgKind::Block => {
+				let names = self.get_vocabulary(ArgKind::Block);
+				if !names.is_empty() && !names.iter().any(|name| name == word) {
+					return Err(CommandError::UnknownBlock(word.to_string()));
+				}
+				
+				ArgValue::Block(word.to_string())
+			},
+			ArgKind::Command => {
+				if self.find(word).is_none() {
+					return Err(CommandError::UnknownCommand(word.to_string()));
+				}
+				
+				ArgValue::Word(word.to_string())
+			},
+			ArgKind::Word => ArgValue::Word(word.to_string()),
+			ArgKind::Text | ArgKind::Coord => unreachable!(),
+		};
+		
+		Ok((value, rest))
+	}
+}
+
+impl Default for CommandRegistry {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Completes command lines from a copy of the commands in a `CommandRegistry`.
+#[derive(Clone, Default)]
+pub struct Completer {
+	known: Arc<RwLock<Known>>,
+}
+
+#[derive(Default)]
+struct Known {
+	commands: Vec<Command>,
+	vocabulary: HashMap<ArgKind, Vec<String>>,
+}
+
+impl Completer {
+	/// Returns the candidates for the word at the end of the given line,
+	/// and the position in the line where that word starts.
+	pub fn complete(&self, line: &str) -> (usize, Vec<String>) {
+		let known = match self.known.read() {
+			Ok(known) => known,
+			Err(_) => return (line.len(), vec![]),
+		};
+		
+		// The word being completed starts after the last whitespace, which may be more than one byte long.
+		let start = line.char_indices()
+			.rfind(|(_, c)| c.is_whitespace())
+			.map(|(mid, c)| mid + c.len_utf8())
+			.unwrap_or(0);
+		let prefix = &line[start..];
+		
+		let mut words = line[..start].split_whitespace();
+		
+		let kind = match words.next() {
+			None => ArgKind::Command,
+			Some(name) => {
+				let command = match known.commands.iter().find(|command| command.is_named(name)) {
+					Some(command) => command,
+					None => return (start, vec![]),
+				};
+				
+				// Find the argument the cursor is in, given the words before it.
+				let mut index = words.count();
+				let mut kind = None;
+				
+				for arg in command.args.iter() {
+					let width = match arg.kind {
+						ArgKind::Coord => 3,
+						ArgKind::Text => usize::MAX,
+						_ => 1,
+					};
+					
+					if index < width {
+						kind = Some(arg.kind);
+						break;
+					}
+					
+					index -= width;
+				}
+				
+				match kind {
+					Some(kind) => kind,
+					None => return (start, vec![]),
+				}
+			}
+		};
+		
+		let mut candidates: Vec<String> = known.vocabulary.get(&kind)
+			.map(|words| words.iter()
+				.filter(|word| word.starts_with(prefix))
+				.cloned()
+				.collect()
+			)
+			.unwrap_or_default();
+		
+		candidates.sort();
+		candidates.dedup();
+		(start, candidates)
+	}
+}
+
+/// Splits the next whitespace-separated word off the given string.
+fn next_word(line: &str) -> (&str, &str) {
+	match line.find(char::is_whitespace) {
+		Some(mid) => (&line[..mid], line[mid..].trim_start()),
+		None => (line, ""),
+	}
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A message from a command to the console it was entered in.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Response {
+	/// Additional output, like a line of help.
+	Info(String),
+	
+	/// The command succeeded.
+	Success(String),
+	
+	/// The command failed.
+	Failure(String),
+}
+
+impl std::fmt::Display for Response {
+	/// Formats the response as a line of the remote console protocol.
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+		match self {
+			Response::Info(message) => write!(f, "INFO {}", message),
+			Response::Success(message) => write!(f, "OK {}", message),
+			Response::Failure(message) => write!(f, "ERR {}", message),
+		}
+	}
+}
+
+/// Where the responses to a command go.
+#[derive(Clone)]
+pub struct ResponseSink {
+	target: Arc<dyn Fn(Response) + Send + Sync>,
+}
+
+impl ResponseSink {
+	pub fn new<F: Fn(Response) + Send + Sync + 'static>(target: F) -> Self {
+		Self {
+			target: Arc::new(target)
+		}
+	}
+	
+	/// Writes responses to the log, for consoles that show it anyway.
+	pub fn log() -> Self {
+		Self::new(|response| match response {
+			Response::Info(message) => info!("{}", message),
+			Response::Success(message) => info!("{}", message),
+			Response::Failure(message) => error!("{}", message),
+		})
+	}
+	
+	/// Collects responses into a list, for scripted tests.
+	pub fn collect() -> (Self, Arc<Mutex<Vec<Response>>>) {
+		let responses = Arc::new(Mutex::new(vec![]));
+		let list = responses.clone();
+		
+		let sink = Self::new(move |response| {
+			if let Ok(mut list) = list.lock() {
+				list.push(response);
+			}
+		});
+		
+		(sink, responses)
+	}
+	
+	pub fn respond(&self, response: Response) {
+		(self.target)(response)
+	}
+	
+	pub fn info<S: Into<String>>(&self, message: S) {
+		self.respond(Response::Info(message.into()))
+	}
+	
+	pub fn success<S: Into<String>>(&self, message: S) {
+		self.respond(Response::Success(message.into()))
+	}
+	
+	pub fn failure<S: Into<String>>(&self, message: S) {
+		self.respond(Response::Failure(message.into()))
+	}
+}
+
+impl Default for ResponseSink {
+	fn default() -> Self {
+		Self::log()
+	}
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Errors that may occur when parsing or executing a command.
+#[derive(Clone, PartialEq, Debug)]
+pub enum CommandError {
+	/// The line was empty.
+	Empty,
+	
+	/// No command is registered under the given name.
+	UnknownCommand(String),
+	
+	/// A required argument was not given.
+	MissingArgument {
+		command: &'static str,
+		argument: &'static str,
+	},
+	
+	/// An argument could not be parsed as its kind.
+	InvalidArgument {
+		command: &'static str,
+		argument: &'static str,
+		expected: ArgKind,
+		value: String,
+	},
+	
+	/// A block argument named a block that does not exist.
+	UnknownBlock(String),
+	
+	/// More arguments were given than the command takes.
+	TooManyArguments {
+		command: &'static str,
+		extra: String,
+	},
+	
+	/// The command was parsed, but could not be executed.
+	Failed(String),
+}
+
+impl std::fmt::Display for CommandError {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+		match self {
+			CommandError::Empty => write!(f, "No command given"),
+			CommandError::UnknownCommand(name) => write!(f, "Unknown command '{}', try 'help'", name),
+			CommandError::MissingArgument {command, argument} => write!(f, "{}: Missing argument <{}>", command, argument),
+			CommandError::InvalidArgument {command, argument, expected, value} => write!(f, "{}: Expected {} for <{}>, got '{}'", command, expected, argument, value),
+			CommandError::UnknownBlock(name) => write!(f, "Unknown block '{}'", name),
+			CommandError::TooManyArguments {command, extra} => write!(f, "{}: Unexpected '{}'", command, extra),
+			CommandError::Failed(reason) => write!(f, "{}", reason),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	fn registry() -> CommandRegistry {
+		let mut registry = CommandRegistry::new();
+		registry.register(Command::new("set-tps", "Changes the tick-rate.")
+			.alias("tps")
+			.optional("rate", ArgKind::Int)
+		);
+		registry.register_scoped("/playground", Command::new("set-block", "Places a block.")
+			.arg("position", ArgKind::Coord)
+			.arg("block", ArgKind::Block)
+		);
+		registry.register(Command::new("echo", "Prints the given text.")
+			.arg("text", ArgKind::Text)
+		);
+		registry
+	}
+	
+	#[test]
+	fn parse_typed_arguments() {
+		let mut registry = registry();
+		
+		let tps = registry.parse("tps 30").unwrap();
+		assert_eq!(tps.get_name(), "set-tps");
+		assert_eq!(tps.get_int("rate"), Some(30));
+		assert_eq!(registry.parse("set-tps").unwrap().get_int("rate"), None);
+		
+		let echo = registry.parse("echo  hello   world ").unwrap();
+		assert_eq!(echo.get_str("text"), Some("hello   world"));
+		
+		registry.set_vocabulary(ArgKind::Block, vec!["air".to_string(), "stone".to_string()]);
+		let set = registry.parse("set-block 1 -2 3 stone").unwrap();
+		assert_eq!(set.get_coord("position"), Some((1, -2, 3)));
+		assert_eq!(set.get_str("block"), Some("stone"));
+	}
+	
+	#[test]
+	fn parse_errors() {
+		let mut registry = registry();
+		registry.set_vocabulary(ArgKind::Block, vec!["air".to_string()]);
+		
+		assert_eq!(registry.parse("  ").err(), Some(CommandError::Empty));
+		assert_eq!(registry.parse("nope").err(), Some(CommandError::UnknownCommand("nope".to_string())));
+		assert!(matches!(registry.parse("tps fast"), Err(CommandError::InvalidArgument {argument: "rate", expected: ArgKind::Int, ..})));
+		assert!(matches!(registry.parse("tps 1 2"), Err(CommandError::TooManyArguments {..})));
+		assert!(matches!(registry.parse("set-block 1 2"), Err(CommandError::MissingArgument {argument: "position", ..})));
+		assert!(matches!(registry.parse("set-block 1 2 3"), Err(CommandError::MissingArgument {argument: "block", ..})));
+		assert_eq!(registry.parse("set-block 1 2 3 lava").err(), Some(CommandError::UnknownBlock("lava".to_string())));
+		
+		registry.unregister_scope("/playground");
+		assert!(matches!(registry.parse("set-block 1 2 3 air"), Err(CommandError::UnknownCommand(_))));
+	}
+	
+	#[test]
+	fn shadowing() {
+		let mut registry = registry();
+		registry.register_scoped("/menu", Command::new("echo", "Echoes into the menu."));
+		assert_eq!(registry.find("echo").unwrap().get_help(), "Echoes into the menu.");
+		
+		// The shadowed command is restored with the scope that replaced it.
+		registry.unregister_scope("/menu");
+		assert_eq!(registry.find("echo").unwrap().get_help(), "Prints the given text.");
+		
+		// A command removed with its own scope is not restored by others.
+		registry.register_scoped("/menu", Command::new("set-block", "Not a block."));
+		registry.unregister_scope("/playground");
+		registry.unregister_scope("/menu");
+		assert!(registry.find("set-block").is_none());
+	}
+	
+	#[test]
+	fn complete() {
+		let mut registry = registry();
+		let completer = registry.completer();
+		registry.set_vocabulary(ArgKind::Block, vec!["stone".to_string(), "sand".to_string(), "air".to_string()]);
+		
+		assert_eq!(completer.complete("se"), (0, vec!["set-block".to_string(), "set-tps".to_string()]));
+		assert_eq!(completer.complete("help t").1, vec!["tps".to_string()]);
+		assert_eq!(completer.complete("set-block 1 2 3 s"), (16, vec!["sand".to_string(), "stone".to_string()]));
+		
+		// Coordinates and unknown commands have nothing to offer.
+		assert!(completer.complete("set-block 1 ").1.is_empty());
+		assert!(completer.complete("nope a").1.is_empty());
+		
+		// Whitespace outside of ASCII separates words too.
+		assert_eq!(completer.complete("set-block 1 2 3\u{a0}s"), (17, vec!["sand".to_string(), "stone".to_string()]));
+		assert_eq!(completer.complete("\u{3000}").0, 3);
+		
+		registry.unregister_scope("/playground");
+		assert_eq!(completer.complete("se").1, vec!["set-tps".to_string()]);
+	}
+	
+	#[test]
+	fn responses() {
+		let (sink, responses) = ResponseSink::collect();
+		sink.info("working");
+		sink.clone().success("done");
+		sink.failure(CommandError::UnknownBlock("lava".to_string()).to_string());
+		
+		let lines: Vec<String> = responses.lock().unwrap().iter().map(|r| r.to_string()).collect();
+		assert_eq!(lines, vec!["INFO working", "OK done", "ERR Unknown block 'lava'"]);
+	}
+	
+	#[test]
+	fn help() {
+		let registry = registry();
+		let all = registry.help(None).unwrap();
+		assert_eq!(all.len(), 4);
+		assert!(all[0].starts_with("echo <text>"));
+		
+		let tps = registry.help(Some("tps")).unwrap();
+		assert_eq!(tps[0], "set-tps [rate] - Changes the tick-rate.");
+		assert!(registry.help(Some("nope")).is_err());
+	}
+}
@@ -0,0 +1,489 @@
+//! The state of an interactive console, independent of how it is drawn.
+//!
+//! A `Console` edits a single input line and keeps a history of entered commands,
+//! while its `Scrollback` collects command responses and log output from any thread.
+
+use std::collections::VecDeque;
+use std::sync::{mpsc, Arc, Mutex};
+
+use super::commandline::Input;
+use super::commands::{Completer, Response, ResponseSink};
+
+/// What a line in the scrollback came from.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LineKind {
+	/// A command that was entered.
+	Input,
+	Info,
+	Success,
+	Failure,
+	Log(log::Level),
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Line {
+	pub kind: LineKind,
+	pub text: String,
+}
+
+/// A bounded list of lines, that can be written to from any thread.
+#[derive(Clone)]
+pub struct Scrollback {
+	lines: Arc<Mutex<VecDeque<Line>>>,
+	capacity: usize,
+}
+
+impl Scrollback {
+	/// Creates a scrollback that keeps the given number of lines.
+	pub fn new(capacity: usize) -> Self {
+		Self {
+			lines: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+			capacity: capacity.max(1),
+		}
+	}
+	
+	/// Appends a line, forgetting the oldest one if the scrollback is full.
+	pub fn push<S: Into<String>>(&self, kind: LineKind, text: S) {
+		let mut lines = match self.lines.lock() {
+			Ok(lines) => lines,
+			Err(_) => return,
+		};
+		
+		if lines.len() >= self.capacity {
+			lines.pop_front();
+		}
+		
+		lines.push_back(Line {
+			kind,
+			text: text.into()
+		});
+	}
+	
+	pub fn len(&self) -> usize {
+		self.lines.lock().map(|lines| lines.len()).unwrap_or(0)
+	}
+	
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+	
+	pub fn clear(&self) {
+		if let Ok(mut lines) = self.lines.lock() {
+			lines.clear();
+		}
+	}
+	
+	/// Returns up to `count` lines, ending `skip` lines before the newest one.
+	pub fn get_lines(&self, count: usize, skip: usize) -> Vec<Line> {
+		let lines = match self.lines.lock() {
+			Ok(lines) => lines,
+			Err(_) => return vec![],
+		};
+		
+		let end = lines.len().saturating_sub(skip);
+		let start = end.saturating_sub(count);
+		lines.range(start..end).cloned().collect()
+	}
+	
+	/// Returns a sink that writes command responses into this scrollback.
+	pub fn sink(&self) -> ResponseSink {
+		let scrollback = self.clone();
+		ResponseSink::new(move |response| match response {
+			Response::Info(text) => scrollback.push(LineKind::Info, text),
+			Response::Success(text) => scrollback.push(LineKind::Success, text),
+			Response::Failure(text) => scrollback.push(LineKind::Failure, text),
+		})
+	}
+}
+
+/// A logger that writes log output into a scrollback.
+pub struct ScrollbackLogger {
+	pub scrollback: Scrollback,
+	pub level: log::LevelFilter,
+}
+
+impl log::Log for ScrollbackLogger {
+	fn enabled(&self, metadata: &log::Metadata) -> bool {
+		metadata.level() <= self.level
+	}
+	
+	fn log(&self, record: &log::Record) {
+		if self.enabled(record.metadata()) {
+			self.scrollback.push(LineKind::Log(record.level()), record.args().to_string());
+		}
+	}
+	
+	fn flush(&self) {}
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// An input line with history and completion, plus a scrollback.
+pub struct Console {
+	open: bool,
+	
+	input: String,
+	
+	/// The position of the cursor in `input`, in bytes.
+	cursor: usize,
+	
+	history: Vec<String>,
+	
+	/// The entry of the history being shown, and the input from before browsing it.
+	browsing: Option<(usize, String)>,
+	
+	scrollback: Scrollback,
+	
+	/// How many lines the view is scrolled up from the newest one.
+	scroll: usize,
+	
+	/// Where entered commands are sent.
+	commands: Option<mpsc::Sender<Input>>,
+	
+	completer: Option<Completer>,
+}
+
+impl Console {
+	pub fn new(scrollback: Scrollback) -> Self {
+		Self {
+			open: false,
+			input: String::new(),
+			cursor: 0,
+			history: vec![],
+			browsing: None,
+			scrollback,
+			scroll: 0,
+			commands: None,
+			completer: None,
+		}
+	}
+	
+	/// Sends entered commands into the given queue, usually the one of a `CommandLine`.
+	pub fn connect(&mut self, commands: mpsc::Sender<Input>) {
+		self.commands = Some(commands);
+	}
+	
+	pub fn set_completer(&mut self, completer: Completer) {
+		self.completer = Some(completer);
+	}
+	
+	pub fn is_open(&self) -> bool {
+		self.open
+	}
+	
+	pub fn set_open(&mut self, open: bool) {
+		self.open = open;
+	}
+	
+	/// Opens or closes the console, returning whether it is open now.
+	pub fn toggle(&mut self) -> bool {
+		self.open = !self.open;
+		self.open
+	}
+	
+	pub fn get_input(&self) -> &str {
+		self.input.as_str()
+	}
+	
+	pub fn get_cursor(&self) -> usize {
+		self.cursor
+	}
+	
+	pub fn get_scrollback(&self) -> &Scrollback {
+		&self.scrollback
+	}
+	
+	/// Inserts a character at the cursor, ignoring control characters.
+	pub fn insert(&mut self, c: char) {
+		if c.is_control() {
+			return;
+		}
+		
+		self.input.insert(self.cursor, c);
+		self.cursor += c.len_utf8();
+	}
+	
+	pub fn insert_str(&mut self, text: &str) {
+		for c in text.chars() {
+			self.insert(c);
+		}
+	}
+	
+	/// Removes the character before the cursor.
+	pub fn backspace(&mut self) {
+		if let Some(c) = self.input[..self.cursor].chars().next_back() {
+			self.cursor -= c.len_utf8();
+			self.input.remove(self.cursor);
+		}
+	}
+	
+	/// Removes the character after the cursor.
+	pub fn delete(&mut self) {
+		if self.cursor < self.input.len() {
+			self.input.remove(self.cursor);
+		}
+	}
+	
+	pub fn left(&mut self) {
+		if let Some(c) = self.input[..self.cursor].chars().next_back() {
+			self.cursor -= c.len_utf8();
+		}
+	}
+	
+	pub fn right(&mut self) {
+		if let Some(c) = self.input[self.cursor..].chars().next() {
+			self.cursor += c.len_utf8();
+		}
+	}
+	
+	pub fn home(&mut self) {
+		self.cursor = 0;
+	}
+	
+	pub fn end(&mut self) {
+		self.cursor = self.input.len();
+	}
+	
+	/// Replaces the input with the previous entry of the history.
+	pub fn history_up(&mut self) {
+		let index = match &self.browsing {
+			Some((0, _)) => return,
+			Some((index, _)) => index - 1,
+			None if self.history.is_empty() => return,
+			None => {
+				self.browsing = Some((self.history.len(), self.input.clone()));
+				self.history.len() - 1
+			}
+		};
+		
+		if let Some((browsing, _)) = self.browsing.as_mut() {
+			*browsing = index;
+		}
+		
+		self.set_input(self.history[index].clone());
+	}
+	
+	/// Replaces the input with the next entry of the history,
+	/// or the input from before browsing it.
+	pub fn history_down(&mut self) {
+		let (index, saved) = match self.browsing.take() {
+			Some(browsing) => browsing,
+			None => return,
+		};
+		
+		if index + 1 < self.history.len() {
+			self.browsing = Some((index + 1, saved));
+			self.set_input(self.history[index + 1].clone());
+		} else {
+			self.set_input(saved);
+		}
+	}
+	
+	fn set_input(&mut self, input: String) {
+		self.input = input;
+		self.cursor = self.input.len();
+	}
+	
+	/// Completes the word before the cursor.
+	///
+	/// A single candidate is inserted; multiple candidates are listed,
+	/// and the input is extended by their common prefix.
+	pub fn complete(&mut self) {
+		let completer = match self.completer.as_ref() {
+			Some(completer) => completer,
+			None => return,
+		};
+		
+		let (start, candidates) = completer.complete(&self.input[..self.cursor]);
+		
+		let replacement = match candidates.len() {
+			0 => return,
+			1 => format!("{} ", candidates[0]),
+			_ => {
+				self.scrollback.push(LineKind::Info, candidates.join("  "));
+				common_prefix(&candidates).to_string()
+			}
+		};
+		
+		if replacement.len() < self.cursor - start {
+			return;
+		}
+		
+		self.input.replace_range(start..self.cursor, &replacement);
+		self.cursor = start + replacement.len();
+	}
+	
+	/// Enters the current input as command, returning it.
+	///
+	/// The command is sent to the connected queue, with this scrollback as response sink.
+	pub fn submit(&mut self) -> Option<String> {
+		let line = std::mem::take(&mut self.input).trim().to_string();
+		self.cursor = 0;
+		self.browsing = None;
+		self.scroll = 0;
+		
+		if line.is_empty() {
+			return None;
+		}
+		
+		self.scrollback.push(LineKind::Input, format!("> {}", line));
+		
+		if self.history.last() != Some(&line) {
+			self.history.push(line.clone());
+		}
+		
+		if let Some(commands) = self.commands.as_ref() {
+			let input = Input {
+				line: line.clone(),
+				sink: self.scrollback.sink(),
+			};
+			
+			if commands.send(input).is_err() {
+				self.scrollback.push(LineKind::Failure, "The command queue is gone.");
+			}
+		}
+		
+		Some(line)
+	}
+	
+	/// Scrolls the view up by the given number of lines, towards older ones.
+	pub fn scroll_up(&mut self, lines: usize) {
+		let max = self.scrollback.len().saturating_sub(1);
+		self.scroll = (self.scroll + lines).min(max);
+	}
+	
+	/// Scrolls the view down by the given number of lines, towards newer ones.
+	pub fn scroll_down(&mut self, lines: usize) {
+		self.scroll = self.scroll.saturating_sub(lines);
+	}
+	
+	/// Returns the lines to show in a view with the given number of rows, oldest first.
+	pub fn get_visible_lines(&self, rows: usize) -> Vec<Line> {
+		self.scrollback.get_lines(rows, self.scroll)
+	}
+}
+
+fn common_prefix(words: &[String]) -> &str {
+	let first = match words.first() {
+		Some(first) => first.as_str(),
+		None => return "",
+	};
+	
+	let mut len = first.len();
+	for word in words[1..].iter() {
+		len = first.char_indices()
+			.zip(word.chars())
+			.take_while(|((_, a), b)| a == b)
+			.map(|((i, a), _)| i + a.len_utf8())
+			.last()
+			.unwrap_or(0)
+			.min(len);
+	}
+	
+	&first[..len]
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::commands::{CommandRegistry, Command};
+	
+	#[test]
+	fn edit_input() {
+		let mut console = Console::new(Scrollback::new(10));
+		console.insert_str("echo hllo");
+		console.left();
+		console.left();
+		console.left();
+		console.insert('e');
+		assert_eq!(console.get_input(), "echo hello");
+		
+		console.home();
+		console.delete();
+		console.end();
+		console.backspace();
+		console.insert('\n');
+		assert_eq!(console.get_input(), "cho hell");
+		assert_eq!(console.get_cursor(), "cho hell".len());
+		
+		// Multi-byte characters are edited as a whole.
+		console.insert('ä');
+		console.left();
+		console.right();
+		console.backspace();
+		assert_eq!(console.get_input(), "cho hell");
+	}
+	
+	#[test]
+	fn history() {
+		let mut console = Console::new(Scrollback::new(10));
+		console.insert_str("first");
+		console.submit();
+		console.insert_str("second");
+		console.submit();
+		console.insert_str("draft");
+		
+		console.history_up();
+		assert_eq!(console.get_input(), "second");
+		console.history_up();
+		console.history_up();
+		assert_eq!(console.get_input(), "first");
+		console.history_down();
+		assert_eq!(console.get_input(), "second");
+		console.history_down();
+		assert_eq!(console.get_input(), "draft");
+		console.history_down();
+		assert_eq!(console.get_input(), "draft");
+	}
+	
+	#[test]
+	fn submit_and_scroll() {
+		let scrollback = Scrollback::new(3);
+		let mut console = Console::new(scrollback.clone());
+		
+		let (sender, receiver) = mpsc::channel();
+		console.connect(sender);
+		
+		console.insert_str("  set-tps 30 ");
+		assert_eq!(console.submit().as_deref(), Some("set-tps 30"));
+		assert_eq!(console.submit(), None);
+		
+		let input = receiver.try_recv().unwrap();
+		assert_eq!(input.line, "set-tps 30");
+		input.sink.success("Changed tick-rate");
+		input.sink.failure("Nope");
+		
+		let lines = console.get_visible_lines(10);
+		assert_eq!(lines.len(), 3);
+		assert_eq!(lines[0], Line {kind: LineKind::Input, text: "> set-tps 30".to_string()});
+		assert_eq!(lines[1].kind, LineKind::Success);
+		
+		// The oldest line is dropped once the scrollback is full.
+		scrollback.push(LineKind::Log(log::Level::Info), "log");
+		console.scroll_up(5);
+		let lines = console.get_visible_lines(1);
+		assert_eq!(lines[0].text, "Changed tick-rate");
+		
+		console.scroll_down(1);
+		assert_eq!(console.get_visible_lines(1)[0].text, "Nope");
+	}
+	
+	#[test]
+	fn complete() {
+		let mut registry = CommandRegistry::new();
+		registry.register(Command::new("set-tps", ""));
+		registry.register(Command::new("set-block", ""));
+		
+		let mut console = Console::new(Scrollback::new(10));
+		console.set_completer(registry.completer());
+		
+		console.insert_str("s");
+		console.complete();
+		assert_eq!(console.get_input(), "set-");
+		assert_eq!(console.get_scrollback().len(), 1);
+		
+		console.insert('t');
+		console.complete();
+		assert_eq!(console.get_input(), "set-tps ");
+	}
+}
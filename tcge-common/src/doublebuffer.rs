@@ -1,10 +1,13 @@
+/// Two instances of `T`, one being written to and one being read from.
+///
+/// Calling `swap` exchanges the roles of the two instances.
 pub struct DblBuf<T> {
 	a: T,
 	b: T,
 	f: Flip,
 }
 
-impl<'d, T> DblBuf<T> {
+impl<T> DblBuf<T> {
 	pub fn new(a: T, b: T) -> Self {
 		Self {a, b, f: Flip::A}
 	}
@@ -13,17 +16,25 @@ impl<'d, T> DblBuf<T> {
 		self.f = !self.f;
 	}
 	
-	pub fn get_writer(&mut self) -> &'d mut T {
+	pub fn get_writer(&mut self) -> &mut T {
 		match &self.f {
-			Flip::A => unsafe {std::mem::transmute(&mut self.a)},
-			Flip::B => unsafe {std::mem::transmute(&mut self.b)},
+			Flip::A => &mut self.a,
+			Flip::B => &mut self.b,
 		}
 	}
 	
-	pub fn get_reader(&self) -> &'d T {
+	pub fn get_reader(&self) -> &T {
 		match &self.f {
-			Flip::A => unsafe {std::mem::transmute(&self.b)},
-			Flip::B => unsafe {std::mem::transmute(&self.a)},
+			Flip::A => &self.b,
+			Flip::B => &self.a,
+		}
+	}
+	
+	/// Borrows the writer and the reader at the same time.
+	pub fn split(&mut self) -> (&mut T, &T) {
+		match &self.f {
+			Flip::A => (&mut self.a, &self.b),
+			Flip::B => (&mut self.b, &self.a),
 		}
 	}
 }
@@ -42,3 +53,26 @@ impl std::ops::Not for Flip {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn swap_semantics() {
+		let mut buf = DblBuf::new(0, 0);
+		*buf.get_writer() = 1;
+		assert_eq!(*buf.get_reader(), 0);
+		
+		buf.swap();
+		assert_eq!(*buf.get_reader(), 1);
+		*buf.get_writer() = 2;
+		assert_eq!(*buf.get_reader(), 1);
+		
+		let (writer, reader) = buf.split();
+		*writer += *reader;
+		buf.swap();
+		assert_eq!(*buf.get_reader(), 3);
+		assert_eq!(*buf.get_writer(), 1);
+	}
+}
@@ -1,3 +1,5 @@
+use super::clock::Clock;
+
 /// Creates a new gameloop with the given number of ticks per second.
 pub fn new(ticks_per_second: i32) -> State {
 	State::new(ticks_per_second)
@@ -38,6 +40,21 @@ pub struct State {
 	/// The time when the last x-per-second measurements where done.
 	last_chk: f64,
 	
+	/// The simulated time, which ticks are scheduled by.
+	game_time: f64,
+	
+	/// The real time when `game_time` was last advanced.
+	last_time: f64,
+	
+	/// How much faster than real time the simulation runs.
+	time_scale: f64,
+	
+	/// Whether the simulation is paused; frames still happen.
+	paused: bool,
+	
+	/// The number of ticks to do while paused.
+	steps: u32,
+	
 	state: LoopState,
 }
 
@@ -56,6 +73,11 @@ impl State {
 			last_chk: 0.0,
 			total_frames: 0,
 			total_ticks: 0,
+			game_time: 0.0,
+			last_time: 0.0,
+			time_scale: 1.0,
+			paused: false,
+			steps: 0,
 			state: LoopState::Pre,
 		}
 	}
@@ -66,9 +88,66 @@ impl State {
 		self.skip_ticks = 1.0 / (ticks_per_second as f64);
 	}
 	
-	pub fn update<TIME>(&mut self, time: TIME) -> LoopState
-		where TIME: Fn() -> f64
-	{
+	/// Stops ticking, while frames continue to be rendered.
+	pub fn pause(&mut self) {
+		self.paused = true;
+	}
+	
+	/// Continues ticking after a `pause`, without catching up on the paused time.
+	pub fn resume(&mut self) {
+		self.paused = false;
+		self.steps = 0;
+	}
+	
+	pub fn is_paused(&self) -> bool {
+		self.paused
+	}
+	
+	/// Pauses the gameloop and does exactly one more tick.
+	pub fn step(&mut self) {
+		self.step_n(1);
+	}
+	
+	/// Pauses the gameloop and does the given number of ticks more, spread over the coming frames.
+	pub fn step_n(&mut self, ticks: u32) {
+		self.paused = true;
+		self.steps = self.steps.saturating_add(ticks);
+	}
+	
+	/// Changes how fast the simulation runs compared to real time, e.g. `0.25` or `4.0`.
+	///
+	/// Returns `false` and keeps the current scale if the given one is not finite and positive.
+	pub fn set_time_scale(&mut self, time_scale: f64) -> bool {
+		if !time_scale.is_finite() || time_scale <= 0.0 {
+			return false
+		}
+		
+		self.time_scale = time_scale;
+		true
+	}
+	
+	pub fn get_time_scale(&self) -> f64 {
+		self.time_scale
+	}
+	
+	/// Returns the simulated time in seconds, which stands still while paused.
+	pub fn get_game_time(&self) -> f64 {
+		self.game_time
+	}
+	
+	/// Moves the simulated time along with the real time.
+	fn advance(&mut self, now: f64) {
+		if !self.paused {
+			self.game_time += (now - self.last_time) * self.time_scale;
+		}
+		
+		self.last_time = now;
+	}
+	
+	/// Advances the gameloop to its next state, reading the time from the given clock.
+	pub fn update<C: Clock>(&mut self, clock: &C) -> LoopState {
+		let time = || clock.now_seconds();
+		
 		self.state = match self.state {
 			LoopState::Pre => {
 				self.loops = 0;
@@ -76,9 +155,19 @@ impl State {
 			},
 			
 			LoopState::TickCheck => {
-				let time = time();
-				if (time > self.next_game_tick) && (self.loops < self.max_frameskip) {
-					LoopState::Tick(self.ticks_per_second, time, self.skip_ticks as f32)
+				self.advance(time());
+				
+				if self.loops >= self.max_frameskip {
+					LoopState::FrameCheck
+				} else if self.paused {
+					if self.steps > 0 {
+						self.steps -= 1;
+						LoopState::Tick(self.ticks_per_second, self.game_time, self.skip_ticks as f32)
+					} else {
+						LoopState::Paused
+					}
+				} else if self.game_time > self.next_game_tick {
+					LoopState::Tick(self.ticks_per_second, self.game_time, self.skip_ticks as f32)
 				} else {
 					LoopState::FrameCheck
 				}
@@ -89,13 +178,25 @@ impl State {
 				self.loops += 1;
 				self.tick_count += 1;
 				self.total_ticks += 1;
+				
+				if self.paused {
+					// A single step moves the simulated time by exactly one tick.
+					self.game_time += self.skip_ticks;
+				}
+				
 				LoopState::TickCheck
 			},
 			
+			LoopState::Paused => {
+				LoopState::FrameCheck
+			},
+			
 			LoopState::FrameCheck => {
 				let now = time();
-				let delta = now - self.next_game_tick;
+				self.advance(now);
+				let delta = self.game_time - self.next_game_tick;
 				
+				// Stands still while paused, since the simulated time does.
 				let interpolation = (delta + self.skip_ticks) / self.skip_ticks;
 				LoopState::Frame(now, interpolation)
 			}
@@ -176,8 +277,13 @@ pub enum LoopState {
 	TickCheck,
 	
 	/// Tells the client to compute a new world-state.
+	///
+	/// Carries the tick-rate, the simulated time and the simulated duration of the tick.
 	Tick(i32, f64, f32),
 	
+	/// Returned instead of ticks while the gameloop is paused.
+	Paused,
+	
 	/// Prepares the information for the Frame-state.
 	FrameCheck,
 	
@@ -197,7 +303,134 @@ pub enum LoopState {
 // These are an important part of the public interface.
 pub use LoopState::Pre;
 pub use LoopState::Tick;
+pub use LoopState::Paused;
 pub use LoopState::Frame;
 pub use LoopState::Timer;
 pub use LoopState::Post;
 pub use LoopState::Stop;
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::clock::ManualClock;
+	
+	/// Runs the gameloop through one frame, returning the number of ticks and whether a `Timer` happened.
+	fn run_frame(state: &mut State, clock: &ManualClock) -> (u64, Option<(f64, f64)>) {
+		let mut ticks = 0;
+		let mut timer = None;
+		
+		assert!(matches!(state.update(clock), LoopState::TickCheck));
+		
+		loop {
+			match state.update(clock) {
+				Tick(_, _, _) => ticks += 1,
+				Timer(fps, tps) => timer = Some((fps, tps)),
+				Pre => return (ticks, timer),
+				Stop => panic!("gameloop stopped"),
+				_ => ()
+			}
+		}
+	}
+	
+	#[test]
+	fn ticks_catch_up() {
+		// 8 ticks per second keep every duration exact.
+		let clock = ManualClock::new();
+		let mut state = State::new(8);
+		
+		clock.advance_seconds(0.375);
+		assert_eq!(run_frame(&mut state, &clock).0, 3);
+		
+		// No time passed, so there is nothing to catch up.
+		assert_eq!(run_frame(&mut state, &clock).0, 0);
+		
+		clock.advance_seconds(0.125);
+		assert_eq!(run_frame(&mut state, &clock).0, 1);
+		assert_eq!(state.get_total_ticks(), 4);
+		assert_eq!(state.get_total_frames(), 3);
+	}
+	
+	#[test]
+	fn frameskip_is_limited() {
+		let clock = ManualClock::new();
+		let mut state = State::new(8);
+		
+		// Lagging two seconds behind is caught up over several frames.
+		clock.advance_seconds(2.0);
+		assert_eq!(run_frame(&mut state, &clock).0, 5);
+		assert_eq!(run_frame(&mut state, &clock).0, 5);
+		assert_eq!(run_frame(&mut state, &clock).0, 5);
+		assert_eq!(run_frame(&mut state, &clock).0, 1);
+		assert_eq!(run_frame(&mut state, &clock).0, 0);
+	}
+	
+	#[test]
+	fn pause_and_step() {
+		let clock = ManualClock::new();
+		let mut state = State::new(8);
+		
+		clock.advance_seconds(0.125);
+		assert_eq!(run_frame(&mut state, &clock).0, 1);
+		
+		state.pause();
+		clock.advance_seconds(1.0);
+		assert_eq!(run_frame(&mut state, &clock).0, 0);
+		assert_eq!(state.get_game_time(), 0.125);
+		
+		state.step();
+		state.step();
+		assert_eq!(run_frame(&mut state, &clock).0, 2);
+		
+		state.step_n(u32::max_value());
+		state.step_n(1);
+		assert_eq!(state.steps, u32::max_value());
+		state.steps = 0;
+		assert_eq!(run_frame(&mut state, &clock).0, 0);
+		assert_eq!(state.get_game_time(), 0.375);
+		
+		// The paused time is not caught up on.
+		state.resume();
+		clock.advance_seconds(0.125);
+		assert_eq!(run_frame(&mut state, &clock).0, 1);
+		assert_eq!(state.get_total_ticks(), 4);
+	}
+	
+	#[test]
+	fn time_scale() {
+		let clock = ManualClock::new();
+		let mut state = State::new(8);
+		
+		assert!(state.set_time_scale(4.0));
+		clock.advance_seconds(0.125);
+		assert_eq!(run_frame(&mut state, &clock).0, 4);
+		
+		assert!(state.set_time_scale(0.25));
+		clock.advance_seconds(0.5);
+		assert_eq!(run_frame(&mut state, &clock).0, 1);
+		assert_eq!(state.get_game_time(), 0.625);
+		
+		assert!(!state.set_time_scale(-1.0));
+		assert!(!state.set_time_scale(0.0));
+		assert!(!state.set_time_scale(std::f64::INFINITY));
+		assert!(!state.set_time_scale(std::f64::NAN));
+		assert_eq!(state.get_time_scale(), 0.25);
+	}
+	
+	#[test]
+	fn timer_reports_rates() {
+		let clock = ManualClock::new();
+		let mut state = State::new(8);
+		
+		// The timer needs at least one second and more than ten frames.
+		for _ in 0..10 {
+			clock.advance_seconds(0.125);
+			assert_eq!(run_frame(&mut state, &clock), (1, None));
+		}
+		
+		clock.advance_seconds(0.125);
+		assert_eq!(run_frame(&mut state, &clock), (1, Some((8.0, 8.0))));
+		
+		clock.advance_seconds(0.125);
+		assert_eq!(run_frame(&mut state, &clock), (1, None));
+	}
+}
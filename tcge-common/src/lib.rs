@@ -1,10 +1,18 @@
 #[macro_use] extern crate log;
 extern crate walkdir;
 extern crate time;
+extern crate rustyline;
+extern crate rustyline_derive;
 
 pub mod doublebuffer;
+pub mod triplebuffer;
 
 pub mod commandline;
+pub mod commands;
+pub mod console;
+
+pub mod clock;
+pub use clock::Clock;
 
 pub mod gameloop;
 
@@ -14,6 +22,9 @@ pub mod resources;
 pub use resources::Resources;
 pub use resources::ResourceLocation;
 
+pub mod assets;
+pub use assets::Assets;
+
 pub fn current_time_nanos() -> u128 {
 	use std::time::{SystemTime, UNIX_EPOCH};
 	let start = SystemTime::now();
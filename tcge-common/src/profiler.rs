@@ -1,40 +1,191 @@
+//! A hierarchical frame profiler.
+//!
+//! Every thread records into its own `ProfilerTree`.
+//! The main thread brackets each frame with `start_frame` and `end_frame`;
+//! other threads (like the IO workers of the `AsyncLoader`) call `submit_thread` whenever they finished a unit of work,
+//! and their trees are merged into the main threads tree at the next `end_frame`.
+
 use super::doublebuffer::DblBuf;
-use std::cell::{UnsafeCell};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::marker::PhantomData;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, AtomicU64, Ordering};
+use std::io::Write;
 
 ////////////////////////////////////////////////////////////////////////////////
 
-pub fn profiler() -> &'static mut Profiler {
-	PROF.with(|p|
-		// This is VERY unsafe.
-		unsafe { std::mem::transmute(p.get())}
-	)
+/// The name of a profiler node.
+pub type Name = Cow<'static, str>;
+
+/// The number of frames the rolling min/avg/max statistics cover.
+///
+/// The statistics of a node that wasn't visited for as many frames are dropped.
+pub const STATS_FRAMES: usize = 120;
+
+/// The number of nodes a tree may have; scopes entered beyond that are not recorded.
+///
+/// A full tree is rebuilt at the start of the next frame, dropping the nodes no longer in use.
+pub const MAX_NODES: usize = 1000;
+
+/// The number of submitted trees kept while no frame ends to merge them.
+const MAX_SUBMITTED: usize = 256;
+
+/// Stands in for a node on the stack of a tree, when a scope was not recorded.
+const DROPPED: usize = usize::MAX;
+
+thread_local! {
+	static PROF: RefCell<Profiler> = RefCell::new(Profiler::new());
 }
 
-pub fn start_frame() {
-	profiler().start_frame()
+/// Trees submitted by other threads, waiting to be merged by `end_frame`.
+static SUBMITTED: Mutex<Vec<ProfilerTree>> = Mutex::new(Vec::new());
+
+/// The number of captures in progress; while non-zero, all threads record trace events.
+static CAPTURING: AtomicUsize = AtomicUsize::new(0);
+
+/// Counter for `THREAD_ID`.
+static NEXT_THREAD_ID: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+	/// A small number identifying the current thread in trace events.
+	static THREAD_ID: u64 = NEXT_THREAD_ID.fetch_add(1, Ordering::Relaxed);
 }
 
-pub fn scope<F, V>(name: &'static str, func: F) -> V
-	where F: FnOnce() -> V {
-	let current = profiler().get_current();
-	current.enter_noguard(name);
-	let v = func();
-	current.leave();
-	v
+pub fn start_frame() {
+	PROF.with(|p| p.borrow_mut().start_frame())
 }
 
 pub fn end_frame() {
-	profiler().end_frame()
+	let submitted = match SUBMITTED.lock() {
+		Ok(mut submitted) => std::mem::take(&mut *submitted),
+		Err(_) => vec![],
+	};
+	
+	PROF.with(|p| p.borrow_mut().end_frame(submitted))
 }
 
-////////////////////////////////////////////////////////////////////////////////
+/// Enters a scope with the given name, which is left when the guard is dropped.
+pub fn enter<N: Into<Name>>(name: N) -> ScopeGuard {
+	let (depth, epoch) = PROF.with(|p| {
+		let mut p = p.borrow_mut();
+		let tree = p.get_current();
+		tree.enter_noguard(name);
+		(tree.stack.len(), p.epoch)
+	});
+	
+	ScopeGuard {
+		depth,
+		epoch,
+		_not_send: PhantomData
+	}
+}
 
-thread_local! {
-	pub static PROF: UnsafeCell<Profiler> = UnsafeCell::new(Profiler::new());
+pub fn scope<N, F, V>(name: N, func: F) -> V
+	where N: Into<Name>, F: FnOnce() -> V {
+	let _guard = enter(name);
+	func()
 }
 
+/// Hands the tree of the current thread over to the main thread, to be merged at the next `end_frame`.
+///
+/// The timings show up as a node named after the thread.
+/// Must not be called on the main thread.
+pub fn submit_thread() {
+	let name = std::thread::current().name()
+		.unwrap_or("unnamed thread")
+		.to_string();
+	
+	let tree = PROF.with(|p| {
+		let mut p = p.borrow_mut();
+		p.epoch += 1;
+		let tree = p.get_current();
+		let mut submitted = tree.clone();
+		submitted.nodes[0].name = name.into();
+		tree.reset();
+		submitted
+	});
+	
+	if let Ok(mut submitted) = SUBMITTED.lock() {
+		// Without frames, nobody takes the trees.
+		if submitted.len() < MAX_SUBMITTED {
+			submitted.push(tree);
+		}
+	}
+}
+
+/// Starts recording every scope of every thread for the given number of frames.
+///
+/// Once done, the capture can be retrieved with `capture_take`.
+pub fn capture_start(frames: usize) {
+	PROF.with(|p| p.borrow_mut().capture_start(frames))
+}
+
+/// Returns true if a capture is in progress.
+pub fn capture_running() -> bool {
+	PROF.with(|p| p.borrow().capture.is_some())
+}
+
+/// Returns the last completed capture, if there is one that hasn't been taken yet.
+pub fn capture_take() -> Option<Capture> {
+	PROF.with(|p| p.borrow_mut().captured.take())
+}
+
+/// Calls the given function with the tree of the last completed frame of this thread.
+///
+/// The profiler can't be entered from within the function.
+pub fn with_last_frame<F, V>(func: F) -> V
+	where F: FnOnce(&ProfilerTree) -> V {
+	PROF.with(|p| func(p.borrow().get_passive()))
+}
+
+/// Leaves its scope when dropped; see `enter`.
+///
+/// Dropping a guard also leaves every scope entered after it that is still open.
+/// A guard that outlives its frame (or the submission of its thread) does nothing.
+/// Guards can't be sent to other threads, since each thread has its own tree.
+pub struct ScopeGuard {
+	depth: usize,
+	epoch: u64,
+	_not_send: PhantomData<*const ()>,
+}
+
+impl Drop for ScopeGuard {
+	fn drop(&mut self) {
+		PROF.with(|p| {
+			let mut p = p.borrow_mut();
+			
+			// The scope was already left when its frame ended.
+			if p.epoch != self.epoch {
+				return;
+			}
+			
+			let tree = p.get_current();
+			while tree.stack.len() >= self.depth && tree.stack.len() > 1 {
+				tree.leave();
+			}
+		})
+	}
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
 pub struct Profiler {
-	dblbuf: DblBuf<ProfilerTree>
+	dblbuf: DblBuf<ProfilerTree>,
+	stats: HashMap<String, RollingStats>,
+	
+	/// The number of frames that ended so far.
+	frame: u64,
+	
+	/// Changes whenever the current tree is reset or swapped, so that older guards know they are stale.
+	epoch: u64,
+	
+	/// The capture in progress and the number of frames it still has to record.
+	capture: Option<(Capture, usize)>,
+	
+	/// The last completed capture.
+	captured: Option<Capture>,
 }
 
 impl Profiler {
@@ -42,7 +193,12 @@ impl Profiler {
 		let a = ProfilerTree::new();
 		let b = ProfilerTree::new();
 		Self {
-			dblbuf: DblBuf::new(a, b)
+			dblbuf: DblBuf::new(a, b),
+			stats: HashMap::new(),
+			frame: 0,
+			epoch: 0,
+			capture: None,
+			captured: None,
 		}
 	}
 	
@@ -55,26 +211,139 @@ impl Profiler {
 	}
 	
 	pub fn start_frame(&mut self) {
+		self.epoch += 1;
+		
 		// Clear out old data!
-		self.get_current().reset();
+		let tree = self.get_current();
+		if tree.nodes.len() >= MAX_NODES {
+			*tree = ProfilerTree::new();
+		}
+		tree.reset();
+		tree.nodes[0].enter();
+		tree.record(0, TracePhase::Begin);
+	}
+	
+	pub fn capture_start(&mut self, frames: usize) {
+		if self.capture.is_none() {
+			CAPTURING.fetch_add(1, Ordering::Relaxed);
+		}
+		
+		info!("Capturing profiler events for {} frames...", frames);
+		self.capture = Some((Capture::default(), frames));
 	}
 	
-	pub fn end_frame(&mut self) {
-		self.get_current().leave();
+	pub fn end_frame(&mut self, mut submitted: Vec<ProfilerTree>) {
+		self.epoch += 1;
+		let tree = self.dblbuf.get_writer();
+		
+		while tree.stack.len() > 1 {
+			tree.leave();
+		}
+		tree.leave();
+		
+		for other in submitted.iter() {
+			tree.merge(0, other, 0);
+		}
+		
+		if let Some((capture, frames)) = &mut self.capture {
+			capture.frames += 1;
+			capture.events.append(&mut tree.events);
+			for other in submitted.iter_mut() {
+				capture.events.append(&mut other.events);
+			}
+			
+			*frames = frames.saturating_sub(1);
+			if *frames == 0 {
+				CAPTURING.fetch_sub(1, Ordering::Relaxed);
+				self.captured = self.capture.take().map(|(capture, _)| capture);
+				info!("Profiler capture complete.");
+			}
+		}
+		
+		// Update the statistics of every node that was visited in this frame.
+		self.frame += 1;
+		let frame = self.frame;
+		
+		let paths = tree.paths();
+		for (node, path) in tree.nodes.iter_mut().zip(paths) {
+			if node.calls == 0 {
+				continue;
+			}
+			
+			let stats = self.stats.entry(path).or_default();
+			stats.push(node.total_time, frame);
+			node.min_time = stats.min();
+			node.avg_time = stats.avg();
+			node.max_time = stats.max();
+		}
+		
+		self.stats.retain(|_, stats| frame - stats.last_seen < STATS_FRAMES as u64);
+		
 		self.dblbuf.swap();
 	}
 }
 
+impl Default for Profiler {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// The total times of a node over the last `STATS_FRAMES` frames it was visited in.
+#[derive(Default)]
+struct RollingStats {
+	samples: VecDeque<u64>,
+	
+	/// The frame the last sample was taken in.
+	last_seen: u64,
+}
+
+impl RollingStats {
+	fn push(&mut self, sample: u64, frame: u64) {
+		if self.samples.len() >= STATS_FRAMES {
+			self.samples.pop_front();
+		}
+		self.samples.push_back(sample);
+		self.last_seen = frame;
+	}
+	
+	fn min(&self) -> u64 {
+		self.samples.iter().copied().min().unwrap_or(0)
+	}
+	
+	fn max(&self) -> u64 {
+		self.samples.iter().copied().max().unwrap_or(0)
+	}
+	
+	fn avg(&self) -> u64 {
+		if self.samples.is_empty() {
+			return 0;
+		}
+		self.samples.iter().sum::<u64>() / self.samples.len() as u64
+	}
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Clone)]
 pub struct ProfilerTree {
 	pub nodes: Vec<ProfilerNode>,
 	pub stack: Vec<usize>,
+	
+	/// The number of scopes not recorded in this frame, because the tree is full.
+	pub dropped: usize,
+	
+	/// Events recorded while a capture is running.
+	pub events: Vec<TraceEvent>,
 }
 
 impl ProfilerTree {
 	pub fn new() -> Self {
 		Self {
-			nodes: vec![ ProfilerNode::new(None, "root") ],
+			nodes: vec![ ProfilerNode::new(None, "root".into()) ],
 			stack: vec![ 0 ],
+			dropped: 0,
+			events: vec![],
 		}
 	}
 	
@@ -83,59 +352,134 @@ impl ProfilerTree {
 			node.reset();
 		}
 		
+		self.stack.clear();
 		self.stack.push(0);
-		self.nodes[0].enter();
+		self.dropped = 0;
+		self.events.clear();
 	}
 	
-	pub fn enter(&mut self, name: &'static str) -> ProfilerGuard {
-		self.enter_noguard(name);
-		ProfilerGuard(self, true)
-	}
-	
-	pub fn enter_noguard(&mut self, name: &'static str) {
-		if self.nodes.len() > 1000 {
-			panic!("This aint' supposed to happen...");
-		}
+	/// Enters a child scope of the current one, which must be left with `leave`.
+	///
+	/// If the tree is full, the scope (and everything entered within it) is left out.
+	pub fn enter_noguard<N: Into<Name>>(&mut self, name: N) {
+		let curr = *self.stack.last().unwrap();
 		
-		let mut curr = *self.stack.last().unwrap();
+		let next = if curr == DROPPED {
+			None
+		} else {
+			self.get_or_create_child(curr, name.into())
+		};
 		
-		if self.nodes[curr].name != name {
-			let mut next: Option<usize> = None;
-			for child in self.nodes[curr].childs.iter() {
-				if self.nodes[*child].name == name {
-					next = Some(*child);
-				}
-			}
-			
-			if let Some(next) = next {
-				// Reuse the node...
-				self.stack.push(next);
-				curr = next;
-			}
-			else {
-				// Create a new node...
-				let next = self.nodes.len();
-				self.nodes[curr].childs.push(next);
-				self.nodes.push(ProfilerNode::new(Some(curr), name));
-				self.stack.push(next);
-				curr = next;
+		let next = match next {
+			Some(next) => next,
+			None => {
+				self.stack.push(DROPPED);
+				self.dropped += 1;
+				return;
 			}
-		}
+		};
 		
-		self.nodes[curr].enter();
+		self.stack.push(next);
+		self.nodes[next].enter();
+		self.record(next, TracePhase::Begin);
 	}
 	
 	pub fn leave(&mut self) {
 		let curr = *self.stack.last().unwrap();
+		
+		if curr == DROPPED {
+			self.stack.pop();
+			return;
+		}
+		
+		self.record(curr, TracePhase::End);
+		
 		let curr = &mut self.nodes[curr];
 		
 		curr.leave();
 		
-		if let Some(_) = curr.parent {
+		if curr.parent.is_some() {
 			self.stack.pop();
 		}
 	}
 	
+	fn record(&mut self, node: usize, phase: TracePhase) {
+		if CAPTURING.load(Ordering::Relaxed) == 0 {
+			return;
+		}
+		
+		let thread = std::thread::current();
+		self.events.push(TraceEvent {
+			name: self.nodes[node].name.clone(),
+			phase,
+			time: crate::current_time_nanos_precise(),
+			thread_id: THREAD_ID.with(|id| *id),
+			thread_name: thread.name().unwrap_or("unnamed thread").to_string().into(),
+		});
+	}
+	
+	/// Returns the child of the given node with the given name, creating it unless the tree is full.
+	fn get_or_create_child(&mut self, parent: usize, name: Name) -> Option<usize> {
+		for child in self.nodes[parent].childs.iter() {
+			if self.nodes[*child].name == name {
+				return Some(*child);
+			}
+		}
+		
+		if self.nodes.len() >= MAX_NODES {
+			return None;
+		}
+		
+		let next = self.nodes.len();
+		self.nodes[parent].childs.push(next);
+		self.nodes.push(ProfilerNode::new(Some(parent), name));
+		Some(next)
+	}
+	
+	/// Adds the timings of `other_node` (and all its children) to a child of `node` with the same name.
+	pub fn merge(&mut self, node: usize, other: &ProfilerTree, other_node: usize) {
+		let source = &other.nodes[other_node];
+		let target = match self.get_or_create_child(node, source.name.clone()) {
+			Some(target) => target,
+			None => {
+				self.dropped += 1;
+				return;
+			}
+		};
+		
+		// Nodes that were never entered (like the root of another thread) take the time of their children.
+		let total_time = if source.calls > 0 {
+			source.total_time
+		} else {
+			source.childs.iter().map(|c| other.nodes[*c].total_time).sum()
+		};
+		
+		self.nodes[target].calls += source.calls.max(1);
+		self.nodes[target].total_time += total_time;
+		
+		for child in source.childs.iter() {
+			if other.nodes[*child].calls > 0 {
+				self.merge(target, other, *child);
+			}
+		}
+	}
+	
+	/// Returns the `/`-separated path of names for every node, by index.
+	pub fn paths(&self) -> Vec<String> {
+		let mut paths: Vec<String> = Vec::with_capacity(self.nodes.len());
+		
+		// Children are always created after their parents.
+		for node in self.nodes.iter() {
+			let path = match node.parent {
+				Some(parent) => format!("{}/{}", paths[parent], node.name),
+				None => node.name.to_string(),
+			};
+			paths.push(path);
+		}
+		
+		paths
+	}
+	
 	pub fn print(&self) {
 		println!("Timing information for {}:", self.nodes[0].name);
 		for child in &self.nodes[0].childs {
@@ -159,9 +503,16 @@ impl ProfilerTree {
 	}
 }
 
+impl Default for ProfilerTree {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[derive(Clone)]
 pub struct ProfilerNode {
 	// Label
-	pub name: &'static str,
+	pub name: Name,
 	
 	// Structure
 	pub parent: Option<usize>,
@@ -171,10 +522,15 @@ pub struct ProfilerNode {
 	pub calls: u32,
 	pub start_time: u64,
 	pub total_time: u64,
+	
+	// Rolling stats over the last `STATS_FRAMES` frames
+	pub min_time: u64,
+	pub avg_time: u64,
+	pub max_time: u64,
 }
 
 impl ProfilerNode {
-	pub fn new(parent: Option<usize>, name: &'static str) -> Self {
+	pub fn new(parent: Option<usize>, name: Name) -> Self {
 		Self {
 			name,
 			parent,
@@ -182,6 +538,9 @@ impl ProfilerNode {
 			calls: 0,
 			start_time: crate::current_time_nanos_precise(),
 			total_time: 0,
+			min_time: 0,
+			avg_time: 0,
+			max_time: 0,
 		}
 	}
 	
@@ -203,24 +562,99 @@ impl ProfilerNode {
 	pub fn get_time_as_nanosec(&self) -> Nanosec {
 		Nanosec { inner: self.total_time }
 	}
+	
+	/// Returns the rolling min, avg and max times.
+	pub fn get_stats_as_nanosec(&self) -> (Nanosec, Nanosec, Nanosec) {
+		(
+			Nanosec { inner: self.min_time },
+			Nanosec { inner: self.avg_time },
+			Nanosec { inner: self.max_time },
+		)
+	}
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Whether a `TraceEvent` marks the start or end of a scope.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TracePhase {
+	Begin,
+	End,
+}
+
+/// The entering or leaving of a scope, recorded during a capture.
+#[derive(Clone, Debug)]
+pub struct TraceEvent {
+	pub name: Name,
+	pub phase: TracePhase,
+	pub time: u64,
+	pub thread_id: u64,
+	pub thread_name: Name,
+}
+
+/// The events of all threads over a number of frames.
+#[derive(Default)]
+pub struct Capture {
+	pub frames: usize,
+	pub events: Vec<TraceEvent>,
 }
 
-pub struct ProfilerGuard<'a>(&'a mut ProfilerTree, bool);
-impl <'a> ProfilerGuard<'a> {
-	pub fn force_leave(&mut self) {
-		if self.1 {
-			self.0.leave();
-			self.1 = false;
+impl Capture {
+	/// Writes the capture in the Chrome Trace Event format,
+	/// which can be loaded by `chrome://tracing` or Perfetto.
+	pub fn write_chrome_trace<W: Write>(&self, out: &mut W) -> std::io::Result<()> {
+		let start = self.events.iter().map(|e| e.time).min().unwrap_or(0);
+		
+		let mut threads: Vec<(u64, &str)> = self.events.iter()
+			.map(|e| (e.thread_id, e.thread_name.as_ref()))
+			.collect();
+		threads.sort();
+		threads.dedup();
+		
+		write!(out, "{{\"traceEvents\":[")?;
+		
+		let mut first = true;
+		for (id, name) in threads {
+			if !first { write!(out, ",")?; }
+			first = false;
+			write!(out, "\n{{\"name\":\"thread_name\",\"ph\":\"M\",\"pid\":1,\"tid\":{},\"args\":{{\"name\":", id)?;
+			write_json_string(out, name)?;
+			write!(out, "}}}}")?;
 		}
+		
+		for event in self.events.iter() {
+			if !first { write!(out, ",")?; }
+			first = false;
+			
+			let phase = match event.phase {
+				TracePhase::Begin => "B",
+				TracePhase::End => "E",
+			};
+			
+			write!(out, "\n{{\"name\":")?;
+			write_json_string(out, &event.name)?;
+			write!(out, ",\"ph\":\"{}\",\"pid\":1,\"tid\":{},\"ts\":{:.3}}}",
+				phase,
+				event.thread_id,
+				(event.time - start) as f64 / 1000.0
+			)?;
+		}
+		
+		write!(out, "\n],\"displayTimeUnit\":\"ms\"}}")
 	}
 }
-impl <'a> Drop for ProfilerGuard<'a> {
-	fn drop(&mut self) {
-		if self.1 {
-			self.0.leave();
-			self.1 = false;
+
+fn write_json_string<W: Write>(out: &mut W, value: &str) -> std::io::Result<()> {
+	write!(out, "\"")?;
+	for c in value.chars() {
+		match c {
+			'"' => write!(out, "\\\"")?,
+			'\\' => write!(out, "\\\\")?,
+			c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32)?,
+			c => write!(out, "{}", c)?,
 		}
 	}
+	write!(out, "\"")
 }
 
 pub struct Nanosec {
@@ -246,3 +680,144 @@ impl std::fmt::Display for Nanosec {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	fn find(tree: &ProfilerTree, path: &str) -> Option<usize> {
+		tree.paths().iter().position(|p| p == path)
+	}
+	
+	#[test]
+	fn frames_and_threads() {
+		std::thread::Builder::new()
+			.name("profiled".to_string())
+			.spawn(|| {
+				for _ in 0..2 {
+					start_frame();
+					
+					scope("outer", || {
+						let _inner = enter("inner");
+						
+						// A guard that outlives an inner one must not corrupt the stack.
+						let outer_guard = enter("leaked");
+						let _unused = enter("deeper");
+						drop(outer_guard);
+					});
+					
+					let worker = std::thread::Builder::new()
+						.name("worker".to_string())
+						.spawn(|| {
+							scope("mesh", || ());
+							submit_thread();
+						})
+						.unwrap();
+					worker.join().unwrap();
+					
+					end_frame();
+				}
+				
+				with_last_frame(|tree| {
+					assert_eq!(tree.stack, vec![0]);
+					
+					let outer = find(tree, "root/outer").expect("outer node");
+					assert_eq!(tree.nodes[outer].calls, 1);
+					assert!(find(tree, "root/outer/inner/leaked/deeper").is_some());
+					
+					let mesh = find(tree, "root/worker/mesh").expect("merged worker node");
+					assert_eq!(tree.nodes[mesh].calls, 1);
+					assert!(tree.nodes[mesh].min_time <= tree.nodes[mesh].max_time);
+				});
+			})
+			.unwrap()
+			.join()
+			.unwrap();
+	}
+	
+	#[test]
+	fn limits() {
+		std::thread::spawn(|| {
+			start_frame();
+			scope("once", || ());
+			
+			for i in 0..MAX_NODES {
+				let _guard = enter(format!("scope {}", i));
+				scope("nested", || ());
+			}
+			
+			end_frame();
+			
+			with_last_frame(|tree| {
+				assert_eq!(tree.nodes.len(), MAX_NODES);
+				assert_eq!(tree.stack, vec![0]);
+				assert!(tree.dropped > 0);
+			});
+			
+			// The statistics of nodes that are gone expire.
+			for _ in 0..STATS_FRAMES {
+				start_frame();
+				end_frame();
+			}
+			
+			PROF.with(|p| {
+				let p = p.borrow();
+				assert!(!p.stats.contains_key("root/once"));
+				assert!(p.dblbuf.get_reader().nodes.len() < MAX_NODES);
+			});
+		}).join().unwrap();
+	}
+	
+	#[test]
+	fn stale_guards() {
+		std::thread::spawn(|| {
+			start_frame();
+			let held = enter("held");
+			end_frame();
+			
+			start_frame();
+			let outer = enter("outer");
+			
+			// Leaving a scope of the last frame must not leave one of this frame.
+			drop(held);
+			PROF.with(|p| assert_eq!(p.borrow_mut().get_current().stack.len(), 2));
+			
+			let _nested = enter("nested");
+			drop(outer);
+			end_frame();
+			
+			with_last_frame(|tree| {
+				assert_eq!(tree.stack, vec![0]);
+				assert!(find(tree, "root/outer/nested").is_some());
+				assert_eq!(find(tree, "root/held"), None);
+			});
+		}).join().unwrap();
+	}
+	
+	#[test]
+	fn capture_chrome_trace() {
+		std::thread::spawn(|| {
+			capture_start(2);
+			
+			for _ in 0..3 {
+				start_frame();
+				scope("traced \"scope\"", || ());
+				end_frame();
+			}
+			
+			assert!(!capture_running());
+			let capture = capture_take().expect("capture is complete");
+			assert!(capture_take().is_none());
+			assert_eq!(capture.frames, 2);
+			
+			let mut json = vec![];
+			capture.write_chrome_trace(&mut json).unwrap();
+			let json = String::from_utf8(json).unwrap();
+			
+			assert!(json.starts_with("{\"traceEvents\":["));
+			assert_eq!(json.matches("\"name\":\"traced \\\"scope\\\"\"").count(), 4);
+			assert_eq!(json.matches("\"ph\":\"B\"").count(), 4);
+			assert_eq!(json.matches("\"ph\":\"E\"").count(), 4);
+		}).join().unwrap();
+	}
+}
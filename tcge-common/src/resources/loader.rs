@@ -0,0 +1,193 @@
+use super::{Resources, ResourceSnapshot, ResourceProvider, ResourceLocation, ResourceError};
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread;
+
+/// Identifies a single request made to an `AsyncLoader`.
+#[derive(Eq, PartialEq, Hash, Clone, Copy, Debug)]
+pub struct LoadId(u64);
+
+/// The result of a request made to an `AsyncLoader`.
+pub struct Completion {
+	pub id: LoadId,
+	pub location: ResourceLocation,
+	pub result: Result<Vec<u8>, ResourceError>,
+}
+
+struct Job {
+	id: LoadId,
+	location: ResourceLocation,
+	providers: ResourceSnapshot,
+}
+
+/// Reads resources on a pool of background threads.
+///
+/// Requests are answered in the order they complete,
+/// which is not necessarily the order they were made in.
+/// Completions must be polled, usually once per frame.
+pub struct AsyncLoader {
+	jobs: Option<mpsc::Sender<Job>>,
+	completions: mpsc::Receiver<Completion>,
+	workers: Vec<thread::JoinHandle<()>>,
+	next_id: u64,
+	pending: usize,
+}
+
+impl AsyncLoader {
+	/// Creates a loader with the given number of worker threads (at least one).
+	pub fn new(threads: usize) -> Self {
+		let (jobs, job_receiver) = mpsc::channel::<Job>();
+		let (completion_sender, completions) = mpsc::channel();
+		
+		// The workers take turns waiting for the next job.
+		let job_receiver = Arc::new(Mutex::new(job_receiver));
+		
+		let workers = (0..threads.max(1))
+			.map(|i| {
+				let job_receiver = job_receiver.clone();
+				let completion_sender = completion_sender.clone();
+				thread::Builder::new()
+					.name(format!("IO-Worker {}", i))
+					.spawn(move || work(job_receiver, completion_sender))
+					.expect("Failed to spawn IO worker thread.")
+			})
+			.collect();
+		
+		Self {
+			jobs: Some(jobs),
+			completions,
+			workers,
+			next_id: 0,
+			pending: 0,
+		}
+	}
+	
+	/// Requests the given resource to be read into a buffer in the background.
+	pub fn res_as_buffer_async(&mut self, res: &Resources, location: ResourceLocation) -> LoadId {
+		self.next_id += 1;
+		let id = LoadId(self.next_id);
+		
+		let job = Job {
+			id,
+			location,
+			providers: res.snapshot(),
+		};
+		
+		let jobs = self.jobs.as_ref().expect("jobs channel only closes on drop");
+		if let Err(mpsc::SendError(job)) = jobs.send(job) {
+			// All workers died, so the request will never complete.
+			error!("IO workers are gone, unable to load {}", job.location);
+			return id;
+		}
+		
+		self.pending += 1;
+		id
+	}
+	
+	/// Returns the next completed request, if there is one.
+	pub fn poll(&mut self) -> Option<Completion> {
+		match self.completions.try_recv() {
+			Ok(completion) => {
+				self.pending -= 1;
+				Some(completion)
+			},
+			Err(_) => None,
+		}
+	}
+	
+	/// Blocks until the next request completes, returning `None` if there are no pending requests.
+	pub fn wait(&mut self) -> Option<Completion> {
+		if self.pending == 0 {
+			return None;
+		}
+		
+		match self.completions.recv() {
+			Ok(completion) => {
+				self.pending -= 1;
+				Some(completion)
+			},
+			Err(_) => None,
+		}
+	}
+	
+	/// Returns the number of requests that have not been polled yet.
+	pub fn get_pending(&self) -> usize {
+		self.pending
+	}
+}
+
+impl Drop for AsyncLoader {
+	fn drop(&mut self) {
+		// Closing the channel makes the workers finish.
+		self.jobs.take();
+		
+		for worker in self.workers.drain(..) {
+			if worker.join().is_err() {
+				error!("An IO worker thread panicked.");
+			}
+		}
+	}
+}
+
+fn work(jobs: Arc<Mutex<mpsc::Receiver<Job>>>, completions: mpsc::Sender<Completion>) {
+	loop {
+		let job = match jobs.lock() {
+			Ok(jobs) => jobs.recv(),
+			Err(_) => return,
+		};
+		
+		let job = match job {
+			Ok(job) => job,
+			Err(_) => return,
+		};
+		
+		let result = crate::profiler::scope("load", || job.providers.res_as_buffer(&job.location));
+		crate::profiler::submit_thread();
+		
+		let completion = Completion {
+			id: job.id,
+			location: job.location,
+			result,
+		};
+		
+		if completions.send(completion).is_err() {
+			return;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::IncludeProvider;
+	
+	#[test]
+	fn load_in_background() {
+		let mut res = Resources::empty();
+		res.register_provider_by_type(IncludeProvider::new(vec![
+			("core/a.txt", b"a"),
+			("core/b.txt", b"bb"),
+		]));
+		
+		let mut loader = AsyncLoader::new(2);
+		let a = loader.res_as_buffer_async(&res, ResourceLocation::from_static("core:a.txt"));
+		let b = loader.res_as_buffer_async(&res, ResourceLocation::from_static("core:b.txt"));
+		let c = loader.res_as_buffer_async(&res, ResourceLocation::from_static("core:c.txt"));
+		assert_eq!(loader.get_pending(), 3);
+		
+		let mut completions = vec![];
+		while let Some(completion) = loader.wait() {
+			completions.push(completion);
+		}
+		
+		assert_eq!(loader.get_pending(), 0);
+		assert!(loader.poll().is_none());
+		
+		completions.sort_by_key(|c| c.id.0);
+		assert_eq!(completions[0].id, a);
+		assert_eq!(completions[0].result.as_ref().unwrap(), b"a");
+		assert_eq!(completions[1].id, b);
+		assert_eq!(completions[1].result.as_ref().unwrap(), b"bb");
+		assert_eq!(completions[2].id, c);
+		assert!(completions[2].result.is_err());
+	}
+}
@@ -1,45 +1,282 @@
+/// A validated, namespaced location of a resource, written as `domain:path`.
+///
+/// The legacy form `domain/path` is accepted as well,
+/// since it is how resources are laid out on disk and in archives.
+///
+/// The path is always relative to its domain, never empty,
+/// and never contains `.`, `..` or empty segments.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct ResourceLocation {
-	pub inner: String
+	domain: String,
+	path: String,
 }
 
 impl ResourceLocation {
-	pub fn from_str(path: &str) -> Self {
-		Self::from_string(path.to_string())
+	/// Parses and normalizes a location.
+	pub fn parse(location: &str) -> Result<Self, LocationError> {
+		let (domain, path) = match location.find(':') {
+			Some(mid) => (&location[..mid], &location[mid+1..]),
+			None => match location.find('/') {
+				Some(mid) => (&location[..mid], &location[mid+1..]),
+				None => return Err(LocationError::MissingDomain(location.to_string())),
+			}
+		};
+		
+		Self::new(domain, path)
 	}
 	
-	pub fn from_string(path: String) -> Self {
-		Self {
-			inner: path
+	/// Creates a location from the given domain and path.
+	pub fn new(domain: &str, path: &str) -> Result<Self, LocationError> {
+		if domain.is_empty() {
+			return Err(LocationError::MissingDomain(path.to_string()));
+		}
+		
+		if let Some(c) = domain.chars().find(|c| !is_domain_char(*c)) {
+			return Err(LocationError::InvalidCharacter(c));
+		}
+		
+		// The domain is the first segment on disk, so it may not leave the root either.
+		if domain == "." || domain == ".." {
+			return Err(LocationError::Traversal(domain.to_string()));
+		}
+		
+		Ok(Self {
+			domain: domain.to_string(),
+			path: normalize_path(path)?,
+		})
+	}
+	
+	/// Parses the given location, panicking if it is invalid.
+	///
+	/// Only use this for locations that are known at compile-time,
+	/// anything else goes trough `parse` (or `FromStr`/`TryFrom`).
+	pub fn from_static(location: &'static str) -> Self {
+		match Self::parse(location) {
+			Ok(location) => location,
+			Err(err) => panic!("Invalid resource location '{}': {}", location, err),
+		}
+	}
+	
+	pub fn domain(&self) -> &str {
+		self.domain.as_str()
+	}
+	
+	pub fn path(&self) -> &str {
+		self.path.as_str()
+	}
+	
+	/// Returns the segments of the location, starting with the domain.
+	///
+	/// This is how providers should resolve a location.
+	pub fn segments(&self) -> impl Iterator<Item = &str> {
+		std::iter::once(self.domain.as_str())
+			.chain(self.path.split('/'))
+	}
+	
+	/// Returns the location in the `domain/path` form used by providers as key.
+	pub fn to_key(&self) -> String {
+		format!("{}/{}", self.domain, self.path)
+	}
+	
+	/// Returns the name of the last segment of the path.
+	pub fn file_name(&self) -> &str {
+		match self.path.rfind('/') {
+			Some(mid) => &self.path[mid+1..],
+			None => self.path.as_str(),
 		}
 	}
 	
-	pub fn pre(&self, lhs: &str) -> Self {
-		Self {
-			inner: lhs.to_string() + &self.inner
+	/// Returns the extension of the last segment, if there is one.
+	pub fn extension(&self) -> Option<&str> {
+		let name = self.file_name();
+		match name.rfind('.') {
+			Some(0) | None => None,
+			Some(mid) => Some(&name[mid+1..]),
 		}
 	}
 	
-	pub fn add(&self, rhs: &str) -> Self {
-		Self {
-			inner: self.inner.clone() + rhs
+	/// Returns a copy of this location with the given extension appended or replaced.
+	///
+	/// The extension must be a single, non-empty segment without dots.
+	pub fn with_extension(&self, extension: &str) -> Result<Self, LocationError> {
+		if extension.is_empty() {
+			return Err(LocationError::InvalidExtension(extension.to_string()));
+		}
+		
+		if let Some(c) = extension.chars().find(|c| *c == '.' || *c == '/' || *c == '\\' || *c == ':' || c.is_control()) {
+			return Err(LocationError::InvalidCharacter(c));
+		}
+		
+		let stem_len = match self.extension() {
+			Some(ext) => self.path.len() - ext.len() - 1,
+			None => self.path.len(),
+		};
+		
+		Ok(Self {
+			domain: self.domain.clone(),
+			path: format!("{}.{}", &self.path[..stem_len], extension),
+		})
+	}
+	
+	/// Returns the location containing this one, if it isn't the top of its domain.
+	pub fn parent(&self) -> Option<Self> {
+		self.path.rfind('/').map(|mid| Self {
+			domain: self.domain.clone(),
+			path: self.path[..mid].to_string(),
+		})
+	}
+	
+	/// Appends the given relative path to this location.
+	pub fn join(&self, path: &str) -> Result<Self, LocationError> {
+		let path = normalize_path(path)?;
+		Ok(Self {
+			domain: self.domain.clone(),
+			path: format!("{}/{}", self.path, path),
+		})
+	}
+}
+
+fn is_domain_char(c: char) -> bool {
+	c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-' || c == '.'
+}
+
+fn normalize_path(path: &str) -> Result<String, LocationError> {
+	if path.starts_with('/') {
+		return Err(LocationError::Absolute(path.to_string()));
+	}
+	
+	let mut normalized = String::with_capacity(path.len());
+	
+	for segment in path.split('/') {
+		match segment {
+			"" | "." => continue,
+			".." => return Err(LocationError::Traversal(path.to_string())),
+			_ => ()
+		}
+		
+		if let Some(c) = segment.chars().find(|c| *c == '\\' || *c == ':' || c.is_control()) {
+			return Err(LocationError::InvalidCharacter(c));
+		}
+		
+		if !normalized.is_empty() {
+			normalized.push('/');
 		}
+		
+		normalized.push_str(segment);
+	}
+	
+	if normalized.is_empty() {
+		return Err(LocationError::EmptyPath);
+	}
+	
+	Ok(normalized)
+}
+
+impl std::str::FromStr for ResourceLocation {
+	type Err = LocationError;
+	
+	fn from_str(location: &str) -> Result<Self, Self::Err> {
+		Self::parse(location)
 	}
 }
 
-impl From<&str> for ResourceLocation {
-	fn from(path: &str) -> Self {
-		Self::from_str(path)
+impl std::convert::TryFrom<&str> for ResourceLocation {
+	type Error = LocationError;
+	
+	fn try_from(location: &str) -> Result<Self, Self::Error> {
+		Self::parse(location)
 	}
 }
 
-impl From<String> for ResourceLocation {
-	fn from(path: String) -> Self {
-		Self::from_string(path)
+impl std::convert::TryFrom<String> for ResourceLocation {
+	type Error = LocationError;
+	
+	fn try_from(location: String) -> Result<Self, Self::Error> {
+		Self::parse(location.as_str())
 	}
 }
 
 impl std::fmt::Display for ResourceLocation {
 	fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
-		write!(f, "{}", self.inner)
+		write!(f, "{}:{}", self.domain, self.path)
+	}
+}
+
+/// Errors that may occur when parsing a `ResourceLocation`.
+#[derive(Clone, PartialEq, Debug)]
+pub enum LocationError {
+	MissingDomain(String),
+	EmptyPath,
+	Absolute(String),
+	Traversal(String),
+	InvalidCharacter(char),
+	InvalidExtension(String),
+}
+
+impl std::fmt::Display for LocationError {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+		match self {
+			LocationError::MissingDomain(path) => write!(f, "No domain given for '{}'", path),
+			LocationError::EmptyPath => write!(f, "Path is empty"),
+			LocationError::Absolute(path) => write!(f, "Path '{}' is absolute", path),
+			LocationError::Traversal(path) => write!(f, "Path '{}' leaves its domain", path),
+			LocationError::InvalidCharacter(c) => write!(f, "Invalid character {:?}", c),
+			LocationError::InvalidExtension(ext) => write!(f, "Invalid extension '{}'", ext),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn parse_forms() {
+		let a = ResourceLocation::parse("core:shaders/blocks.vert").unwrap();
+		let b = ResourceLocation::parse("core/shaders//./blocks.vert").unwrap();
+		assert_eq!(a, b);
+		assert_eq!(a.domain(), "core");
+		assert_eq!(a.path(), "shaders/blocks.vert");
+		assert_eq!(a.to_key(), "core/shaders/blocks.vert");
+		assert_eq!(a.to_string(), "core:shaders/blocks.vert");
+	}
+	
+	#[test]
+	fn parse_rejects() {
+		assert!(ResourceLocation::parse("playground.toml").is_err());
+		assert!(ResourceLocation::parse("core:../secret").is_err());
+		assert!(ResourceLocation::parse("core:shaders/../../secret").is_err());
+		assert!(ResourceLocation::parse("core:/etc/passwd").is_err());
+		assert!(ResourceLocation::parse("C:\\Windows").is_err());
+		assert!(ResourceLocation::parse("core:").is_err());
+		assert!(ResourceLocation::parse(":shaders/x").is_err());
+		assert!(ResourceLocation::parse("../x").is_err());
+		assert!(ResourceLocation::parse("..:x").is_err());
+		assert!(ResourceLocation::parse("./x").is_err());
+		assert!(ResourceLocation::new("..", "x").is_err());
+		assert!(ResourceLocation::parse("com.example:x").is_ok());
+		
+		use std::convert::TryFrom;
+		assert!(ResourceLocation::try_from("core:../secret").is_err());
+		assert!(ResourceLocation::try_from(String::from("playground.toml")).is_err());
+		assert_eq!("core:a/b".parse::<ResourceLocation>(), ResourceLocation::parse("core/a/b"));
+	}
+	
+	#[test]
+	fn helpers() {
+		let loc = ResourceLocation::from_static("core:fonts/hack/hack.fnt");
+		assert_eq!(loc.extension(), Some("fnt"));
+		assert_eq!(loc.file_name(), "hack.fnt");
+		assert_eq!(loc.with_extension("png").unwrap().path(), "fonts/hack/hack.png");
+		assert!(loc.with_extension("").is_err());
+		assert!(loc.with_extension("png/../x").is_err());
+		assert!(loc.with_extension("tar.gz").is_err());
+		assert_eq!(loc.parent().unwrap().path(), "fonts/hack");
+		assert_eq!(loc.parent().unwrap().join("page.png").unwrap().path(), "fonts/hack/page.png");
+		assert!(loc.join("../../x").is_err());
+		
+		let shader = ResourceLocation::from_static("core:shaders/text");
+		assert_eq!(shader.extension(), None);
+		assert_eq!(shader.with_extension("vert").unwrap().path(), "shaders/text.vert");
 	}
 }
@@ -1,11 +1,18 @@
 pub use std::io::Read;
+use std::sync::mpsc::{channel, Sender, Receiver};
+use std::collections::HashSet;
+use std::sync::Arc;
 
 pub mod location;
 pub use location::ResourceLocation;
+pub use location::LocationError;
 
 pub mod providers;
 pub use providers::*;
 
+pub mod loader;
+pub use loader::*;
+
 pub fn new() -> Resources {
 	Resources::new()
 }
@@ -15,16 +22,44 @@ pub enum ResourceError {
 	Io(std::io::Error),
 	HasNil(usize),
 	NotFound,
+	Location(LocationError),
+	Corrupt(&'static str),
+	Unsupported(&'static str),
 	Unknown,
 }
 
+/// The priority of providers registered without one.
+pub const DEFAULT_PRIORITY: i32 = 0;
+
+/// A registered provider and its priority.
+struct Pack {
+	priority: i32,
+	provider: Arc<dyn ResourceProvider>,
+}
+
+/// Which provider serves a location, and which providers it shadows.
+#[derive(Debug)]
+pub struct Provenance {
+	/// The name of the provider that serves the location, if any.
+	pub served_by: Option<String>,
+	
+	/// The names of all other providers that contain the location, in priority order.
+	pub shadowed: Vec<String>,
+}
+
+/// The set of all resource providers (packs).
+///
+/// Providers are ordered by descending priority;
+/// providers with the same priority are ordered by registration.
+/// The first provider that contains a resource serves it.
 pub struct Resources {
-	providers: Vec<Box<dyn ResourceProvider>>
+	providers: Vec<Pack>,
+	watchers: Vec<Sender<ResourceLocation>>,
 }
 
 impl Resources {
 	pub fn new() -> Self {
-		let mut new = Resources {providers: vec![]};
+		let mut new = Self::empty();
 		
 		let filesystem = FilesystemProvider::from_exe_path()
 			.ok().expect("Could not open filesystem provider.");
@@ -33,12 +68,109 @@ impl Resources {
 		new
 	}
 	
+	/// Creates a set without any providers.
+	pub fn empty() -> Self {
+		Resources {providers: vec![], watchers: vec![]}
+	}
+	
 	pub fn register_provider_by_type<P: 'static + ResourceProvider>(&mut self, provider: P) {
-		self.providers.push(Box::new(provider));
+		self.register_provider(Box::new(provider));
 	}
 	
 	pub fn register_provider(&mut self, provider: Box<dyn ResourceProvider>) {
-		self.providers.push(provider);
+		self.register_provider_with_priority(provider, DEFAULT_PRIORITY);
+	}
+	
+	/// Registers the provider below all providers with the same or a higher priority.
+	pub fn register_provider_with_priority(&mut self, provider: Box<dyn ResourceProvider>, priority: i32) {
+		let index = self.providers.iter()
+			.position(|pack| pack.priority < priority)
+			.unwrap_or(self.providers.len());
+		
+		self.insert_provider(index, provider, priority);
+	}
+	
+	/// Registers the provider directly above the provider with the given name.
+	pub fn register_provider_above(&mut self, name: &str, provider: Box<dyn ResourceProvider>) -> Result<(), ResourceError> {
+		let index = self.find_provider(name).ok_or(ResourceError::NotFound)?;
+		let priority = self.providers[index].priority;
+		self.insert_provider(index, provider, priority);
+		Ok(())
+	}
+	
+	/// Registers the provider directly below the provider with the given name.
+	pub fn register_provider_below(&mut self, name: &str, provider: Box<dyn ResourceProvider>) -> Result<(), ResourceError> {
+		let index = self.find_provider(name).ok_or(ResourceError::NotFound)?;
+		let priority = self.providers[index].priority;
+		self.insert_provider(index + 1, provider, priority);
+		Ok(())
+	}
+	
+	fn insert_provider(&mut self, index: usize, provider: Box<dyn ResourceProvider>, priority: i32) {
+		info!("Registered provider {} with priority {}.", provider.get_internal_name(), priority);
+		self.providers.insert(index, Pack {priority, provider: Arc::from(provider)});
+	}
+	
+	fn find_provider(&self, name: &str) -> Option<usize> {
+		self.providers.iter()
+			.position(|pack| pack.provider.get_internal_name() == name)
+	}
+	
+	/// Returns the names and priorities of all providers, in the order they are queried.
+	pub fn get_providers(&self) -> Vec<(&str, i32)> {
+		self.providers.iter()
+			.map(|pack| (pack.provider.get_internal_name(), pack.priority))
+			.collect()
+	}
+	
+	/// Returns an immutable view of the current providers, that can be sent to other threads.
+	pub fn snapshot(&self) -> ResourceSnapshot {
+		ResourceSnapshot {
+			providers: self.providers.iter()
+				.map(|pack| pack.provider.clone())
+				.collect()
+		}
+	}
+	
+	/// Returns which provider serves the given location, and which ones it shadows.
+	pub fn res_provenance(&self, location: &ResourceLocation) -> Provenance {
+		let mut containing = self.providers.iter()
+			.filter(|pack| pack.provider.res_exists(location))
+			.map(|pack| pack.provider.get_internal_name().to_string());
+		
+		Provenance {
+			served_by: containing.next(),
+			shadowed: containing.collect(),
+		}
+	}
+	
+	/// Returns a receiver that is sent the location of every resource that changes.
+	///
+	/// Changes are only detected when `poll_watchers` is called.
+	pub fn subscribe(&mut self) -> Receiver<ResourceLocation> {
+		let (sender, receiver) = channel();
+		self.watchers.push(sender);
+		receiver
+	}
+	
+	/// Polls all providers for changes and notifies the subscribers.
+	///
+	/// Returns the number of changed resources.
+	pub fn poll_watchers(&mut self) -> usize {
+		if self.watchers.is_empty() {
+			return 0
+		}
+		
+		let changes = self.poll_changes();
+		
+		for location in changes.iter() {
+			info!("Resource changed: {}", location);
+			
+			// Subscribers that went away are dropped.
+			self.watchers.retain(|watcher| watcher.send(location.clone()).is_ok());
+		}
+		
+		changes.len()
 	}
 }
 
@@ -50,8 +182,8 @@ impl ResourceProvider for Resources {
 	fn res_list(&self) -> Result<Box<dyn Iterator<Item = String>>, ResourceError> {
 		let mut iter: Box<dyn Iterator<Item = String>> = Box::new(std::iter::empty());
 		
-		for provider in self.providers.iter() {
-			let sub_iter = match provider.res_list() {
+		for pack in self.providers.iter() {
+			let sub_iter = match pack.provider.res_list() {
 				Err(err) => return Err(err),
 				Ok(iter) => iter,
 			};
@@ -59,14 +191,16 @@ impl ResourceProvider for Resources {
 			iter = Box::new(iter.chain(sub_iter));
 		}
 		
-		Ok(iter)
+		// Shadowed resources are only listed once.
+		let mut seen = HashSet::new();
+		Ok(Box::new(iter.filter(move |path| seen.insert(path.clone()))))
 	}
 	
 	fn res_as_stream(&self, location: &ResourceLocation) -> Result<Box<dyn Read>, ResourceError> {
 		debug!("Attempting to find resource: {}", location);
 		
-		for provider in self.providers.iter() {
-			match provider.res_as_stream(location) {
+		for pack in self.providers.iter() {
+			match pack.provider.res_as_stream(location) {
 				Err(_) => continue,
 				Ok(stream) => {
 					return Ok(stream)
@@ -77,6 +211,54 @@ impl ResourceProvider for Resources {
 		error!("Failed to find resource: {}", location);
 		Err(ResourceError::NotFound)
 	}
+	
+	fn poll_changes(&self) -> Vec<ResourceLocation> {
+		let mut changes: Vec<ResourceLocation> = vec![];
+		
+		for pack in self.providers.iter() {
+			for location in pack.provider.poll_changes() {
+				if !changes.contains(&location) {
+					changes.push(location);
+				}
+			}
+		}
+		
+		changes
+	}
+}
+
+/// The providers of a `Resources` at the time `Resources::snapshot` was called.
+#[derive(Clone)]
+pub struct ResourceSnapshot {
+	providers: Vec<Arc<dyn ResourceProvider>>,
+}
+
+impl ResourceProvider for ResourceSnapshot {
+	fn get_internal_name(&self) -> &str {
+		"Snapshot"
+	}
+	
+	fn res_list(&self) -> Result<Box<dyn Iterator<Item = String>>, ResourceError> {
+		let mut iter: Box<dyn Iterator<Item = String>> = Box::new(std::iter::empty());
+		
+		for provider in self.providers.iter() {
+			iter = Box::new(iter.chain(provider.res_list()?));
+		}
+		
+		// Shadowed resources are only listed once.
+		let mut seen = HashSet::new();
+		Ok(Box::new(iter.filter(move |path| seen.insert(path.clone()))))
+	}
+	
+	fn res_as_stream(&self, location: &ResourceLocation) -> Result<Box<dyn Read>, ResourceError> {
+		for provider in self.providers.iter() {
+			if let Ok(stream) = provider.res_as_stream(location) {
+				return Ok(stream)
+			}
+		}
+		
+		Err(ResourceError::NotFound)
+	}
 }
 
 pub fn get_exe_path() -> Result<std::path::PathBuf, std::io::Error> {
@@ -86,4 +268,41 @@ pub fn get_exe_path() -> Result<std::path::PathBuf, std::io::Error> {
 		.ok_or(std::io::Error::from(std::io::ErrorKind::NotFound))?;
 	
 	Ok(exe_path.into())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	fn pack(name: &str, content: &'static [u8]) -> Box<dyn ResourceProvider> {
+		Box::new(IncludeProvider::with_name(name, vec![
+			("core/a.txt", content),
+			("core/only-in-pack.txt", content),
+		]))
+	}
+	
+	#[test]
+	fn priorities_and_provenance() {
+		let mut res = Resources::empty();
+		res.register_provider(pack("base", b"base"));
+		res.register_provider_with_priority(pack("mod", b"mod"), 10);
+		res.register_provider_above("base", pack("patch", b"patch")).unwrap();
+		res.register_provider_below("mod", pack("fix", b"fix")).unwrap();
+		
+		let order: Vec<&str> = res.get_providers().iter().map(|(n, _)| *n).collect();
+		assert_eq!(order, vec!["mod", "fix", "patch", "base"]);
+		
+		let location = ResourceLocation::from_static("core:a.txt");
+		assert_eq!(res.res_as_string(&location).unwrap(), "mod");
+		
+		let provenance = res.res_provenance(&location);
+		assert_eq!(provenance.served_by.as_deref(), Some("mod"));
+		assert_eq!(provenance.shadowed, vec!["fix", "patch", "base"]);
+		
+		let missing = res.res_provenance(&ResourceLocation::from_static("core:missing.txt"));
+		assert_eq!(missing.served_by, None);
+		assert!(missing.shadowed.is_empty());
+		
+		assert_eq!(res.res_list().unwrap().count(), 2);
+	}
+}
@@ -1,11 +1,147 @@
 extern crate zip;
 
+use super::ResourceLocation;
+use super::ResourceError;
+use std::path::Path;
+use std::io::{Read, Cursor};
+use std::fs::File;
+use std::sync::Mutex;
+use zip::ZipArchive;
+use zip::result::ZipError;
+
+/// How many bytes are reserved up front when reading an entry, whatever size its header claims.
+const MAX_PREALLOCATION: u64 = 1 << 20;
+
+/// A provider that serves resources from the entries of a `.zip` archive.
+///
+/// Entries are looked up by `ResourceLocation::to_key`, so the archive must be laid out
+/// as `domain/path`: the location `core:textures/stone.png` is the entry `core/textures/stone.png`.
 pub struct ArchiveProvider {
-	// TODO: Everything.
+	name: String,
+	archive: Mutex<ZipArchive<File>>,
 }
 
 impl ArchiveProvider {
-	pub fn new() {
-		unimplemented!()
+	/// Opens the zip archive at the given path.
+	pub fn from_path<P: AsRef<Path>>(path: P) -> Result<ArchiveProvider, ResourceError> {
+		let path = path.as_ref();
+		
+		let file = File::open(path)
+			.map_err(ResourceError::Io)?;
+		
+		let archive = ZipArchive::new(file)
+			.map_err(ResourceError::from)?;
+		
+		let name = path.file_name()
+			.and_then(|n| n.to_str())
+			.unwrap_or("Archive")
+			.to_string();
+		
+		info!("Created ArchiveProvider: {} ({} Entries)", path.to_str().unwrap_or("[ERROR]"), archive.len());
+		Ok(ArchiveProvider {
+			name,
+			archive: Mutex::new(archive)
+		})
+	}
+}
+
+impl super::ResourceProvider for ArchiveProvider {
+	fn get_internal_name(&self) -> &str {
+		self.name.as_str()
+	}
+	
+	fn res_list(&self) -> Result<Box<dyn Iterator<Item = String>>, ResourceError> {
+		let archive = self.archive.lock()
+			.map_err(|_| ResourceError::Unknown)?;
+		
+		// Directories are stored as entries too, but are not resources.
+		let iter: Vec<String> = archive.file_names()
+			.filter(|name| !name.ends_with('/'))
+			.map(|name| name.to_string())
+			.collect()
+		;
+		
+		Ok(Box::new(iter.into_iter()))
+	}
+	
+	fn res_exists(&self, location: &ResourceLocation) -> bool {
+		match self.archive.lock() {
+			Ok(mut archive) => archive.by_name(&location.to_key()).is_ok(),
+			Err(_) => false,
+		}
+	}
+	
+	fn res_as_stream(&self, location: &ResourceLocation) -> Result<Box<dyn Read>, ResourceError> {
+		let mut archive = self.archive.lock()
+			.map_err(|_| ResourceError::Unknown)?;
+		
+		let mut entry = archive.by_name(&location.to_key())
+			.map_err(ResourceError::from)?;
+		
+		// The entry borrows the archive, so it has to be read out completely.
+		// The size comes from the archive itself, so it is not trusted with the allocation.
+		let mut buf = Vec::with_capacity(entry.size().min(MAX_PREALLOCATION) as usize);
+		entry.read_to_end(&mut buf)
+			.map_err(ResourceError::Io)?;
+		
+		Ok(Box::new(Cursor::new(buf)))
+	}
+}
+
+impl From<ZipError> for ResourceError {
+	fn from(error: ZipError) -> Self {
+		match error {
+			ZipError::Io(err) => ResourceError::Io(err),
+			ZipError::InvalidArchive(reason) => ResourceError::Corrupt(reason),
+			ZipError::UnsupportedArchive(reason) => ResourceError::Unsupported(reason),
+			ZipError::FileNotFound => ResourceError::NotFound,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::ResourceProvider;
+	use std::io::Write;
+	
+	fn write_archive(path: &Path) {
+		let file = File::create(path).unwrap();
+		let mut writer = zip::ZipWriter::new(file);
+		let options = zip::write::FileOptions::default()
+			.compression_method(zip::CompressionMethod::Stored);
+		
+		writer.add_directory("core/", options).unwrap();
+		writer.start_file("core/hello.txt", options).unwrap();
+		writer.write_all(b"Hello, world!").unwrap();
+		writer.finish().unwrap();
+	}
+	
+	#[test]
+	fn read_entries() {
+		let path = std::env::temp_dir().join(format!("tcge-archive-{}.zip", std::process::id()));
+		write_archive(&path);
+		
+		let provider = ArchiveProvider::from_path(&path).unwrap();
+		let list: Vec<String> = provider.res_list().unwrap().collect();
+		assert_eq!(list, vec!["core/hello.txt"]);
+		
+		let hello = ResourceLocation::from_static("core:hello.txt");
+		assert!(provider.res_exists(&hello));
+		
+		let mut text = String::new();
+		provider.res_as_stream(&hello).unwrap().read_to_string(&mut text).unwrap();
+		assert_eq!(text, "Hello, world!");
+		
+		let missing = ResourceLocation::from_static("core:missing.txt");
+		assert!(!provider.res_exists(&missing));
+		assert!(matches!(provider.res_as_stream(&missing), Err(ResourceError::NotFound)));
+		
+		// Cutting off the central directory at the end breaks the archive.
+		let bytes = std::fs::read(&path).unwrap();
+		std::fs::write(&path, &bytes[..bytes.len() / 2]).unwrap();
+		assert!(matches!(ArchiveProvider::from_path(&path), Err(ResourceError::Corrupt(_))));
+		
+		std::fs::remove_file(&path).unwrap();
 	}
 }
@@ -1,12 +1,18 @@
 use super::ResourceLocation;
 use super::ResourceError;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::io::Read;
 use std::fs::File;
+use std::time::SystemTime;
+use std::collections::HashMap;
+use std::sync::Mutex;
 
+/// Modification times of all files below the root, by their location key.
+type Snapshot = HashMap<String, SystemTime>;
 
 pub struct FilesystemProvider {
-	root_path: PathBuf
+	root_path: PathBuf,
+	snapshot: Mutex<Option<Snapshot>>,
 }
 
 impl FilesystemProvider {
@@ -15,10 +21,48 @@ impl FilesystemProvider {
 			.map_err(ResourceError::Io)?;
 		let fs_path = exe_path.join("assets");
 		
+		Ok(Self::from_path(fs_path))
+	}
+	
+	pub fn from_path<P: AsRef<Path>>(path: P) -> FilesystemProvider {
+		let fs_path = path.as_ref().to_path_buf();
+		
 		info!("Created FilesystemProvider: {}", fs_path.to_str().unwrap_or("[ERROR]"));
-		Ok(FilesystemProvider {
-			root_path: fs_path
-		})
+		FilesystemProvider {
+			root_path: fs_path,
+			snapshot: Mutex::new(None),
+		}
+	}
+	
+	fn resolve(&self, location: &ResourceLocation) -> PathBuf {
+		let mut path: PathBuf = self.root_path.clone();
+		
+		// Instead of passing the path directly...
+		for part in location.segments() {
+			path = path.join(part);
+		}
+		
+		path
+	}
+	
+	fn take_snapshot(&self) -> Snapshot {
+		let root = &self.root_path;
+		walkdir::WalkDir::new(root)
+			.max_depth(5)
+			.same_file_system(true)
+			.into_iter()
+			.filter_map(|e| e.ok())
+			.filter(|e| e.file_type().is_file())
+			.filter_map(|e| {
+				let modified = e.metadata().ok()?.modified().ok()?;
+				let key = e.path().strip_prefix(root).ok()?
+					.components()
+					.map(|c| c.as_os_str().to_str())
+					.collect::<Option<Vec<&str>>>()?
+					.join("/");
+				Some((key, modified))
+			})
+			.collect()
 	}
 }
 
@@ -58,17 +102,75 @@ impl super::ResourceProvider for FilesystemProvider {
 		Ok(Box::new(walker_iter))
 	}
 	
+	fn res_exists(&self, location: &ResourceLocation) -> bool {
+		self.resolve(location).is_file()
+	}
+	
 	fn res_as_stream(&self, location: &ResourceLocation) -> Result<Box<dyn Read>, ResourceError> {
-		let mut path: PathBuf = self.root_path.clone();
-		
-		// Instead of passing the path directly...
-		for part in location.inner.split('/') {
-			path = path.join(part);
-		}
-		
-		let file = File::open(path)
+		let file = File::open(self.resolve(location))
 			.map_err(ResourceError::Io)?;
 		
 		Ok(Box::new(file))
 	}
+	
+	fn poll_changes(&self) -> Vec<ResourceLocation> {
+		let current = self.take_snapshot();
+		
+		let mut snapshot = match self.snapshot.lock() {
+			Ok(snapshot) => snapshot,
+			Err(_) => return vec![],
+		};
+		
+		let previous = match snapshot.replace(current) {
+			// The first poll only records the initial state.
+			None => return vec![],
+			Some(previous) => previous,
+		};
+		
+		let current = snapshot.as_ref().expect("snapshot was just set");
+		
+		let changed = current.iter()
+			.filter(|(key, modified)| previous.get(*key) != Some(modified))
+			.map(|(key, _)| key);
+		
+		let removed = previous.keys()
+			.filter(|key| !current.contains_key(*key));
+		
+		// Files that are not within a domain can't be referenced, so they are ignored.
+		changed.chain(removed)
+			.filter_map(|key| ResourceLocation::parse(key).ok())
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::ResourceProvider;
+	use std::time::Duration;
+	
+	#[test]
+	fn poll_changes() {
+		let root = std::env::temp_dir().join(format!("tcge-fs-watch-{}", std::process::id()));
+		let domain = root.join("core");
+		std::fs::create_dir_all(&domain).unwrap();
+		std::fs::write(domain.join("a.txt"), "a").unwrap();
+		
+		let provider = FilesystemProvider::from_path(&root);
+		assert!(provider.poll_changes().is_empty(), "first poll records the initial state");
+		assert!(provider.poll_changes().is_empty());
+		
+		std::fs::write(domain.join("b.txt"), "b").unwrap();
+		assert_eq!(provider.poll_changes(), vec![ResourceLocation::from_static("core:b.txt")]);
+		
+		let file = File::options().write(true).open(domain.join("a.txt")).unwrap();
+		file.set_modified(SystemTime::now() + Duration::from_secs(10)).unwrap();
+		drop(file);
+		assert_eq!(provider.poll_changes(), vec![ResourceLocation::from_static("core:a.txt")]);
+		
+		std::fs::remove_file(domain.join("b.txt")).unwrap();
+		assert_eq!(provider.poll_changes(), vec![ResourceLocation::from_static("core:b.txt")]);
+		
+		std::fs::remove_dir_all(&root).unwrap();
+	}
 }
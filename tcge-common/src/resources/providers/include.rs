@@ -5,14 +5,20 @@ use std::io::Read;
 pub type Includes = Vec<(&'static str, &'static [u8])>;
 
 pub struct IncludeProvider {
+	name: String,
 	includes: Includes
 }
 
 impl IncludeProvider {
 	pub fn new(includes: Includes) -> Self {
+		Self::with_name("Includes", includes)
+	}
+	
+	pub fn with_name(name: &str, includes: Includes) -> Self {
 		info!("Created IncludeProvider: {} Items", includes.len());
 		
 		Self {
+			name: name.to_string(),
 			includes
 		}
 	}
@@ -20,7 +26,7 @@ impl IncludeProvider {
 
 impl super::ResourceProvider for IncludeProvider {
 	fn get_internal_name(&self) -> &str {
-		"Includes"
+		self.name.as_str()
 	}
 	
 	fn res_list(&self) -> Result<Box<dyn Iterator<Item = String>>, ResourceError> {
@@ -31,10 +37,15 @@ impl super::ResourceProvider for IncludeProvider {
 		Ok(Box::new(iter.into_iter()))
 	}
 	
+	fn res_exists(&self, location: &ResourceLocation) -> bool {
+		let location = location.to_key();
+		self.includes.iter().any(|(name, _bytes)| *name == location)
+	}
+	
 	fn res_as_stream(&self, location: &ResourceLocation) -> Result<Box<dyn Read>, ResourceError> {
-		let location = &location.inner;
+		let location = location.to_key();
 		let file = self.includes.iter()
-			.find(|(name, _bytes)| name == location);
+			.find(|(name, _bytes)| *name == location);
 		
 		match file {
 			None => Err(ResourceError::NotFound),
@@ -12,13 +12,28 @@ pub use include::*;
 pub mod archive;
 pub use archive::*;
 
-pub trait ResourceProvider {
+/// A source of resources.
+///
+/// Providers must be shareable across threads, so that resources can be loaded in the background.
+pub trait ResourceProvider: Send + Sync {
 	fn get_internal_name(&self) -> &str;
 	
 	fn res_list(&self) -> Result<Box<dyn Iterator<Item = String>>, ResourceError>;
 	
 	fn res_as_stream(&self, location: &ResourceLocation) -> Result<Box<dyn Read>, ResourceError>;
 	
+	/// Returns true if the provider contains the given location.
+	fn res_exists(&self, location: &ResourceLocation) -> bool {
+		self.res_as_stream(location).is_ok()
+	}
+	
+	/// Returns all locations that changed since the last call.
+	///
+	/// Providers whose contents can't change return nothing.
+	fn poll_changes(&self) -> Vec<ResourceLocation> {
+		vec![]
+	}
+	
 	fn res_as_buffer(&self, location: &ResourceLocation) -> Result<Vec<u8>, ResourceError> {
 		// If the extra semicolon is removed, the code does not compile, so it gets to stay.
 		#![allow(redundant_semicolons)]
@@ -0,0 +1,162 @@
+//! A lock-free triple buffer, for handing the latest state of something from one thread to another.
+//!
+//! The writer always has a buffer to write into, the reader always has a buffer to read from,
+//! and the third buffer holds the most recently published state in between.
+//! Neither side ever blocks the other; the reader simply skips states it was too slow for.
+
+use std::cell::UnsafeCell;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Set in `Shared::middle` when the middle buffer holds a state the reader hasn't seen yet.
+const FRESH: usize = 0b100;
+
+/// Masks the buffer index out of `Shared::middle`.
+const INDEX: usize = 0b011;
+
+struct Shared<T> {
+	buffers: [UnsafeCell<T>; 3],
+	
+	/// Index of the middle buffer, possibly marked as `FRESH`.
+	middle: AtomicUsize,
+}
+
+// Each buffer is only ever accessed by whichever side currently owns its index,
+// and ownership is exchanged through the atomic `middle`.
+unsafe impl<T: Send> Sync for Shared<T> {}
+
+/// Creates a triple buffer whose buffers all start out as a copy of `initial`.
+pub fn new<T: Clone + Send>(initial: T) -> (TripleWriter<T>, TripleReader<T>) {
+	let shared = Arc::new(Shared {
+		buffers: [
+			UnsafeCell::new(initial.clone()),
+			UnsafeCell::new(initial.clone()),
+			UnsafeCell::new(initial),
+		],
+		middle: AtomicUsize::new(1),
+	});
+	
+	let writer = TripleWriter {
+		shared: shared.clone(),
+		index: 0,
+	};
+	
+	let reader = TripleReader {
+		shared,
+		index: 2,
+	};
+	
+	(writer, reader)
+}
+
+/// The writing half of a triple buffer.
+pub struct TripleWriter<T> {
+	shared: Arc<Shared<T>>,
+	index: usize,
+}
+
+impl<T: Send> TripleWriter<T> {
+	/// Returns the buffer to write the next state into.
+	///
+	/// Its contents are whatever state was last in it, not necessarily the last published one.
+	pub fn get_writer(&mut self) -> &mut T {
+		// SAFETY: The writer owns its index until it is exchanged in `publish`.
+		unsafe { &mut *self.shared.buffers[self.index].get() }
+	}
+	
+	/// Makes the written state available to the reader.
+	pub fn publish(&mut self) {
+		let previous = self.shared.middle.swap(self.index | FRESH, Ordering::AcqRel);
+		self.index = previous & INDEX;
+	}
+}
+
+/// The reading half of a triple buffer.
+pub struct TripleReader<T> {
+	shared: Arc<Shared<T>>,
+	index: usize,
+}
+
+impl<T: Send> TripleReader<T> {
+	/// Returns true if a newer state was published since the last `update`.
+	pub fn has_update(&self) -> bool {
+		self.shared.middle.load(Ordering::Relaxed) & FRESH != 0
+	}
+	
+	/// Takes the latest published state, if there is a newer one.
+	///
+	/// Returns true if the state changed.
+	pub fn update(&mut self) -> bool {
+		if !self.has_update() {
+			return false;
+		}
+		
+		let previous = self.shared.middle.swap(self.index, Ordering::AcqRel);
+		self.index = previous & INDEX;
+		true
+	}
+	
+	/// Returns the state taken by the last `update`.
+	pub fn get_reader(&self) -> &T {
+		// SAFETY: The reader owns its index until it is exchanged in `update`.
+		unsafe { &*self.shared.buffers[self.index].get() }
+	}
+	
+	/// Takes the latest published state and returns it.
+	pub fn read(&mut self) -> &T {
+		self.update();
+		self.get_reader()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn publish_and_read() {
+		let (mut writer, mut reader) = new(0);
+		assert!(!reader.has_update());
+		assert_eq!(*reader.read(), 0);
+		
+		*writer.get_writer() = 1;
+		assert_eq!(*reader.read(), 0, "unpublished states are invisible");
+		writer.publish();
+		assert!(reader.has_update());
+		assert_eq!(*reader.read(), 1);
+		assert!(!reader.update());
+		
+		// Only the latest state is seen if the reader falls behind.
+		*writer.get_writer() = 2;
+		writer.publish();
+		*writer.get_writer() = 3;
+		writer.publish();
+		assert_eq!(*reader.read(), 3);
+		assert_eq!(*reader.read(), 3);
+	}
+	
+	#[test]
+	fn concurrent_access() {
+		const COUNT: u64 = 100_000;
+		let (mut writer, mut reader) = new((0u64, 0u64));
+		
+		let thread = std::thread::spawn(move || {
+			for i in 1..=COUNT {
+				// Both halves must always match, or a torn state was observed.
+				*writer.get_writer() = (i, i * 2);
+				writer.publish();
+			}
+		});
+		
+		let mut last = 0;
+		while last < COUNT {
+			let (a, b) = *reader.read();
+			assert_eq!(a * 2, b);
+			assert!(a >= last, "states must never go backwards");
+			last = a;
+		}
+		
+		thread.join().unwrap();
+		assert!(!reader.has_update());
+	}
+}